@@ -1,10 +1,23 @@
-use crate::constants::TRACK_COUNT;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::constants::{FX_SLOTS, MASTER_INSERT_COUNT, TRACK_COUNT};
+use crate::effects;
 use crate::messages::{RecordSource, TrackDisplay, TransportDisplay};
+use crate::sequencer::drum_kit::{DrumKit, DRUM_PARAM_COUNT, LANE_NAMES};
+use crate::sequencer::clock::TimeSignature;
+use crate::sequencer::note_pattern::NotePattern;
+use crate::sequencer::pattern::{DrumPattern, PatternBank};
+use crate::sequencer::sample_kit::PadConfig;
+use crate::synth::arpeggiator::ArpSettings;
+use crate::synth::engines;
+use crate::synth::patch::{self, Patch};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
     Tape,
     Synth,
+    Notes,
     Drum,
     Mixer,
 }
@@ -13,7 +26,8 @@ impl AppMode {
     pub fn next(self) -> Self {
         match self {
             AppMode::Tape => AppMode::Synth,
-            AppMode::Synth => AppMode::Drum,
+            AppMode::Synth => AppMode::Notes,
+            AppMode::Notes => AppMode::Drum,
             AppMode::Drum => AppMode::Mixer,
             AppMode::Mixer => AppMode::Tape,
         }
@@ -23,12 +37,51 @@ impl AppMode {
         match self {
             AppMode::Tape => "TAPE",
             AppMode::Synth => "SYNTH",
+            AppMode::Notes => "NOTES",
             AppMode::Drum => "DRUM",
             AppMode::Mixer => "MIXER",
         }
     }
 }
 
+/// Instrument owned by a track: its synth engine and remembered settings
+#[derive(Clone)]
+pub struct TrackInstrument {
+    /// Selected synth engine index
+    pub engine: usize,
+    /// Parameter values per engine: [engine][param]
+    pub params: Vec<Vec<f32>>,
+    /// Name of the last loaded or saved patch
+    pub patch_name: String,
+    pub arp: ArpSettings,
+    /// Sample for the sampler engine, kept here so it survives engine switches
+    pub sample: Option<Arc<[f32]>>,
+    pub sample_name: String,
+}
+
+impl TrackInstrument {
+    pub fn new() -> Self {
+        Self {
+            engine: 0,
+            params: (0..engines::ENGINE_COUNT).map(engines::default_params).collect(),
+            patch_name: "INIT".to_string(),
+            arp: ArpSettings::default(),
+            sample: None,
+            sample_name: String::new(),
+        }
+    }
+
+    /// Parameter values of the selected engine
+    pub fn engine_params(&self) -> &[f32] {
+        &self.params[self.engine]
+    }
+
+    /// Snapshot of the engine and its settings as a patch
+    pub fn patch(&self) -> Patch {
+        Patch::from_values(&self.patch_name, self.engine, self.engine_params())
+    }
+}
+
 pub struct AppState {
     pub mode: AppMode,
     pub selected_track: usize,
@@ -39,29 +92,106 @@ pub struct AppState {
     pub levels: [f32; TRACK_COUNT],
     pub peaks: [f32; TRACK_COUNT],
     pub master_level: (f32, f32),
+    /// Aux send edited in the mixer
+    pub selected_send: usize,
+    /// Channel EQ band edited in the mixer
+    pub selected_eq_band: usize,
+    /// Master bus inserts (index into `MASTER_INSERTS`, bypassed) in processing order, as reported by the engine
+    pub master_chain: [(usize, bool); MASTER_INSERT_COUNT],
+    pub selected_master_slot: usize,
+    /// Gain reduction in dB of each master insert
+    pub insert_gr: [f32; MASTER_INSERT_COUNT],
+    /// Limiter gain reduction in dB
+    pub master_gr: f32,
     pub should_quit: bool,
-    /// Selected synth engine index
-    pub synth_engine: usize,
-    /// Synth parameter values
-    pub synth_params: [f32; 4],
+    /// Per-track instruments; the selected track's one plays from the keyboard
+    pub instruments: [TrackInstrument; TRACK_COUNT],
+    /// Synth parameter names per engine: [engine][param]
+    pub synth_param_names: Vec<Vec<String>>,
+    /// Selected synth parameter
+    pub selected_param: usize,
+    /// Factory presets followed by user presets
+    pub presets: Vec<Patch>,
+    /// Preset browser cursor
+    pub selected_preset: Option<usize>,
+    /// Track the sampler copies audio from
+    pub sample_source: usize,
+    /// Last WAV loaded from the samples directory
+    pub sample_file: Option<usize>,
     /// Drum sequencer BPM
     pub bpm: f32,
     /// Selected drum instrument
     pub selected_instrument: usize,
-    /// Drum patterns: [instrument][step]
-    pub drum_patterns: [[bool; 16]; 6],
-    /// Current sequencer step (for display)
+    /// Drum voice parameters: [instrument][param]
+    pub drum_params: Vec<[f32; DRUM_PARAM_COUNT]>,
+    /// Selected drum voice parameter
+    pub selected_drum_param: usize,
+    /// Directory of the loaded sample kit; `None` plays the synthesized voices
+    pub drum_kit_path: Option<PathBuf>,
+    pub drum_kit_name: String,
+    /// `kit.json` settings of each lane's sample pad
+    pub drum_pads: Vec<Option<PadConfig>>,
+    pub drum_mutes: [bool; 6],
+    /// Drum patterns for the project
+    pub pattern_bank: PatternBank,
+    /// Drum pattern being edited
+    pub selected_pattern: usize,
+    /// Drum pattern the sequencer is playing
+    pub playing_pattern: usize,
+    /// Pattern waiting to start on the next bar
+    pub queued_pattern: Option<usize>,
+    pub pattern_clipboard: Option<DrumPattern>,
+    /// Song chain: each entry plays its pattern through once, then the next; loops
+    pub song: Vec<usize>,
+    pub song_mode: bool,
+    /// Song chain entry playing
+    pub song_entry: usize,
+    pub time_signature: TimeSignature,
+    /// Which 16 steps of the drum pattern the grid shows
+    pub step_page: usize,
+    /// Drum step being edited (last toggled or selected)
+    pub selected_step: usize,
+    /// Drum parameter edits lock the selected step instead of changing the voice
+    pub lock_mode: bool,
+    /// Drum keys play instruments (and record them while the tape runs)
+    pub drum_perform: bool,
+    /// How far recorded drum hits are pulled onto the grid, 0-1
+    pub quantize: f32,
+    /// Drum swing, 0.5 (straight) to 0.75
+    pub swing: f32,
+    /// Drum timing and velocity jitter, 0-1
+    pub humanize: f32,
+    /// Steps into the playing drum pattern (for display)
     pub current_step: usize,
+    /// Sequencer steps since the clock started, for multi-bar note patterns
+    pub seq_step: usize,
+    /// Note sequencer patterns per track
+    pub note_patterns: [NotePattern; TRACK_COUNT],
+    /// Pattern each track had before its last clear, for undo
+    pub cleared_notes: [Option<NotePattern>; TRACK_COUNT],
+    /// Note editor cursor step
+    pub note_cursor: usize,
+    /// Note editor cursor pitch (MIDI note)
+    pub note_pitch: u8,
     /// Tape simulation enabled
     pub tape_sim_enabled: bool,
     /// Tape speed multiplier
     pub tape_speed: f32,
     /// Waveform data for display (downsampled per track)
     pub waveform_data: [Vec<f32>; TRACK_COUNT],
-    /// Effect names per track per slot
-    pub effect_names: [[String; 3]; TRACK_COUNT],
+    /// Registry index of the effect in each track's insert slots
+    pub effect_types: [[Option<usize>; FX_SLOTS]; TRACK_COUNT],
+    /// Parameter values of each insert slot's effect
+    pub effect_params: [[Vec<f32>; FX_SLOTS]; TRACK_COUNT],
     /// Effect bypassed per track per slot
-    pub effect_bypassed: [[bool; 3]; TRACK_COUNT],
+    pub effect_bypassed: [[bool; FX_SLOTS]; TRACK_COUNT],
+    /// Insert slot edited in the mixer
+    pub selected_fx_slot: usize,
+    /// Parameter of the selected insert slot being edited
+    pub selected_fx_param: usize,
+    /// Parameter names and ranges per registry effect
+    pub effect_param_names: Vec<Vec<String>>,
+    pub effect_param_ranges: Vec<Vec<(f32, f32)>>,
     /// Recording source
     pub record_source: RecordSource,
 }
@@ -78,22 +208,87 @@ impl AppState {
             levels: [0.0; TRACK_COUNT],
             peaks: [0.0; TRACK_COUNT],
             master_level: (0.0, 0.0),
+            selected_send: 0,
+            selected_eq_band: 0,
+            master_chain: std::array::from_fn(|insert| (insert, true)),
+            selected_master_slot: 0,
+            insert_gr: [0.0; MASTER_INSERT_COUNT],
+            master_gr: 0.0,
             should_quit: false,
-            synth_engine: 0,
-            synth_params: [0.5; 4],
+            instruments: std::array::from_fn(|_| TrackInstrument::new()),
+            synth_param_names: (0..engines::ENGINE_COUNT).map(engines::param_names).collect(),
+            selected_param: 0,
+            presets: patch::factory_presets(),
+            selected_preset: None,
+            sample_source: 0,
+            sample_file: None,
             bpm: 120.0,
             selected_instrument: 0,
-            drum_patterns: [[false; 16]; 6],
+            drum_params: DrumKit::new().params(),
+            selected_drum_param: 0,
+            drum_kit_path: None,
+            drum_kit_name: "SYNTH".to_string(),
+            drum_pads: vec![None; LANE_NAMES.len()],
+            drum_mutes: [false; 6],
+            pattern_bank: PatternBank::new(),
+            selected_pattern: 0,
+            playing_pattern: 0,
+            queued_pattern: None,
+            pattern_clipboard: None,
+            song: Vec::new(),
+            song_mode: false,
+            song_entry: 0,
+            time_signature: TimeSignature::default(),
+            step_page: 0,
+            selected_step: 0,
+            lock_mode: false,
+            drum_perform: false,
+            quantize: 1.0,
+            swing: 0.5,
+            humanize: 0.0,
             current_step: 0,
+            seq_step: 0,
+            note_patterns: std::array::from_fn(|_| NotePattern::new()),
+            cleared_notes: Default::default(),
+            note_cursor: 0,
+            note_pitch: 60,
             tape_sim_enabled: false,
             tape_speed: 1.0,
             waveform_data: [vec![], vec![], vec![], vec![]],
-            effect_names: Default::default(),
-            effect_bypassed: Default::default(),
+            effect_types: [[None; FX_SLOTS]; TRACK_COUNT],
+            effect_params: Default::default(),
+            effect_bypassed: [[false; FX_SLOTS]; TRACK_COUNT],
+            selected_fx_slot: 0,
+            selected_fx_param: 0,
+            effect_param_names: (0..effects::EFFECT_COUNT).map(effects::param_names).collect(),
+            effect_param_ranges: (0..effects::EFFECT_COUNT).map(effects::param_ranges).collect(),
             record_source: RecordSource::Internal,
         }
     }
 
+    /// Instrument of the selected track
+    pub fn instrument(&self) -> &TrackInstrument {
+        &self.instruments[self.selected_track]
+    }
+
+    /// Drum pattern being edited
+    pub fn drum_pattern(&self) -> &DrumPattern {
+        &self.pattern_bank.patterns[self.selected_pattern]
+    }
+
+    /// Drum steps shown per grid page
+    pub const STEP_PAGE_LEN: usize = 16;
+
+    /// Grid pages needed to show the longest lane of the edited pattern
+    pub fn step_pages(&self) -> usize {
+        self.drum_pattern().max_length().div_ceil(Self::STEP_PAGE_LEN)
+    }
+
+    /// Note pattern of the selected track
+    pub fn note_pattern(&self) -> &NotePattern {
+        &self.note_patterns[self.selected_track]
+    }
+
     pub fn position_secs(&self) -> f64 {
         self.position as f64 / crate::constants::SAMPLE_RATE as f64
     }
@@ -7,13 +7,22 @@ use std::sync::{Arc, Mutex};
 use crate::audio::buffer::SharedBuffers;
 use crate::audio::mixer::MixerState;
 use crate::audio::transport::Transport;
-use crate::constants::{SAMPLE_RATE, TRACK_COUNT, TRACK_SAMPLES};
-use crate::effects;
-use crate::messages::{AudioCmd, AudioMsg, RecordSource};
-use crate::sequencer::clock::SequencerClock;
+use crate::constants::{AUX_COUNT, FX_SLOTS, MASTER_INSERTS, MAX_BLOCK_FRAMES, MASTER_INSERT_COUNT, SAMPLE_RATE, TRACK_COUNT, TRACK_SAMPLES};
+use crate::effects::{self, Effect, Sidechain};
+use crate::messages::{AudioCmd, AudioMsg, RecordSource, Retired};
+use crate::sequencer::clock::{SequencerClock, TimeSignature};
 use crate::sequencer::drum_kit::DrumKit;
+use crate::sequencer::groove::Groove;
+use crate::sequencer::note_pattern::NotePlayer;
+use crate::sequencer::pattern::{self, PatternBank, PATTERN_COUNT};
+use crate::synth::arpeggiator::Arpeggiator;
 use crate::synth::engines;
 use crate::synth::SynthEngine;
+use crate::effects::compressor::Compressor;
+use crate::effects::eq::{ChannelEq, ParametricEq};
+use crate::effects::tempo::TempoInfo;
+use crate::effects::gate::Gate;
+use crate::effects::limiter::Limiter;
 use crate::tape::simulation::TapeSimulation;
 
 fn detect_loop_end(buffers: &SharedBuffers, exclude_track: Option<usize>) -> Option<usize> {
@@ -28,8 +37,36 @@ fn detect_loop_end(buffers: &SharedBuffers, exclude_track: Option<usize>) -> Opt
     (max_len > 0).then_some(max_len)
 }
 
-fn samples_per_beat(bpm: f32) -> usize {
-    ((SAMPLE_RATE as f32 * 60.0 / bpm.clamp(40.0, 300.0)) as usize).max(1)
+/// Dry signals of the current chunk that dynamics effects can key from
+struct SidechainKeys {
+    tracks: [Vec<f32>; TRACK_COUNT],
+    drums: Vec<f32>,
+}
+
+impl SidechainKeys {
+    fn get(&self, source: Sidechain) -> &[f32] {
+        match source {
+            Sidechain::Track(t) => &self.tracks[t.min(TRACK_COUNT - 1)],
+            Sidechain::Drums => &self.drums,
+        }
+    }
+}
+
+/// Run a chunk through an effect chain, feeding tempo and sidechain keys first
+fn process_chain<'a>(
+    chain: impl IntoIterator<Item = &'a mut Box<dyn Effect>>,
+    left: &mut [f32],
+    right: &mut [f32],
+    keys: &SidechainKeys,
+    tempo: &TempoInfo,
+) {
+    for fx in chain.into_iter().filter(|fx| !fx.is_bypassed()) {
+        fx.set_transport(tempo);
+        if let Some(source) = fx.sidechain() {
+            fx.set_key(keys.get(source));
+        }
+        fx.process(left, right);
+    }
 }
 
 struct LevelMeter {
@@ -118,32 +155,89 @@ impl AudioEngine {
         let mut report_counter: usize = 0;
         let report_interval = SAMPLE_RATE as usize / 30;
 
-        // Synth engine
-        let mut synth_engine: Box<dyn SynthEngine> = engines::create_engine(0);
+        // Per-track synth engines
+        let mut synth_engines: [Box<dyn SynthEngine>; TRACK_COUNT] =
+            std::array::from_fn(|_| engines::create_engine(0));
+        let mut synth_engine_indices = [0usize; TRACK_COUNT];
+        let mut arps: [Arpeggiator; TRACK_COUNT] = std::array::from_fn(|_| Arpeggiator::new());
 
         // Per-track effect chains
-        let mut effect_chains: [Vec<Box<dyn effects::Effect>>; TRACK_COUNT] = [
-            vec![],
-            vec![],
-            vec![],
-            vec![],
-        ];
-
-        // Drum sequencer
+        let mut effect_chains: [[Option<Box<dyn effects::Effect>>; FX_SLOTS]; TRACK_COUNT] =
+            std::array::from_fn(|_| std::array::from_fn(|_| None));
+        // Fixed 3-band EQ on each mixer strip, ahead of the inserts
+        let mut channel_eqs: [ChannelEq; TRACK_COUNT] = std::array::from_fn(|_| ChannelEq::new());
+        // Aux return chains: a fully wet reverb and delay shared by the sends
+        let mut aux_chains: [Vec<Box<dyn effects::Effect>>; AUX_COUNT] = {
+            let mut reverb = effects::reverb::Reverb::new();
+            reverb.set_param(0, 1.0);
+            reverb.settle();
+            let mut delay = effects::delay::Delay::new();
+            delay.set_param(2, 1.0);
+            // Dotted eighths, following the tempo
+            delay.set_param(3, 8.0);
+            delay.settle();
+            [vec![Box::new(reverb)], vec![Box::new(delay)]]
+        };
+        // Block buffers: tracks and the live monitor are rendered for the whole
+        // callback chunk first, so effects can process it in one pass
+        let block = || Vec::with_capacity(MAX_BLOCK_FRAMES);
+        let mut track_left: [Vec<f32>; TRACK_COUNT] = std::array::from_fn(|_| block());
+        let mut track_right: [Vec<f32>; TRACK_COUNT] = std::array::from_fn(|_| block());
+        let mut aux_left: [Vec<f32>; AUX_COUNT] = std::array::from_fn(|_| block());
+        let mut aux_right: [Vec<f32>; AUX_COUNT] = std::array::from_fn(|_| block());
+        let mut keys = SidechainKeys {
+            tracks: std::array::from_fn(|_| block()),
+            drums: block(),
+        };
+        let mut live_left: Vec<f32> = block();
+        let mut live_right: Vec<f32> = block();
+        let mut frame_playing: Vec<bool> = Vec::with_capacity(MAX_BLOCK_FRAMES);
+
+        // Drum + note sequencers
         let mut drum_kit = DrumKit::new();
+        // 16th-note clock for the note sequencers; drums step at their pattern's resolution
         let mut seq_clock = SequencerClock::new(120.0);
-        let mut drum_patterns = [[false; 16]; 6];
-        // Free-running sample counter for drum preview when transport is stopped
+        let mut drum_clock = SequencerClock::new(120.0);
+        let mut time_signature = TimeSignature::default();
+        let mut last_bar = usize::MAX;
+        // Timeline position the playing drum pattern started from
+        let mut pattern_start: usize = 0;
+        // Swing, nudge and humanize scheduling for the drum steps
+        let mut groove = Groove::new();
+        // How far live drum hits are pulled onto the grid when recorded
+        let mut quantize: f32 = 1.0;
+        let mut last_seq_pos = usize::MAX;
+        let mut pattern_bank = PatternBank::new();
+        let mut current_pattern: usize = 0;
+        let mut queued_pattern: Option<usize> = None;
+        let mut song: Vec<usize> = Vec::new();
+        let mut song_mode = false;
+        // Where the playing song entry ends; `None` finds the entry again from the timeline
+        let mut song_end: Option<usize> = None;
+        // Per-track note sequencer, driving that track's synth engine
+        let mut note_players: [NotePlayer; TRACK_COUNT] = std::array::from_fn(|_| NotePlayer::new());
+        // Free-running sample counter for sequencer preview when transport is stopped
         let mut free_counter: usize = 0;
 
-        // Tape simulation
-        let mut tape_sim = TapeSimulation::new();
+        // Master bus inserts in order (tape sim starts disabled), then the limiter
+        let mut master_chain: [Box<dyn effects::Effect>; MASTER_INSERT_COUNT] = {
+            let mut gate = Gate::new();
+            gate.set_bypass(true);
+            let mut eq = ParametricEq::new();
+            eq.set_bypass(true);
+            let mut comp = Compressor::new();
+            comp.set_bypass(true);
+            [Box::new(gate), Box::new(eq), Box::new(comp), Box::new(TapeSimulation::new())]
+        };
+        let mut master_insert_gr = [0.0f32; MASTER_INSERT_COUNT];
+        let mut limiter = Limiter::new();
+        let mut master_chain_changed = true;
+        let mut master_gr: f32 = 0.0;
 
         // Recording source
         let mut record_source = RecordSource::Internal;
 
-        // Record count-in + metronome
-        let count_in_beats: usize = 4;
+        // Record count-in (one bar) + metronome
         let click_len_samples: usize = (SAMPLE_RATE as usize / 40).max(1); // ~25ms click
         let mut pending_record_track: Option<usize> = None;
         let mut count_in_samples_remaining: usize = 0;
@@ -160,6 +254,7 @@ impl AudioEngine {
             &output_config,
             move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
                 // --- Process commands ---
+                let mut synth_params_changed = [false; TRACK_COUNT];
                 while let Ok(cmd) = cmd_rx.try_recv() {
                     match cmd {
                         AudioCmd::Play => {
@@ -186,11 +281,19 @@ impl AudioEngine {
                             click_samples_remaining = 0;
                             transport.stop();
                             seq_clock.reset();
+                            drum_clock.reset();
+                            last_bar = usize::MAX;
+                            pattern_start = 0;
+                            song_end = None;
+                            for (player, engine) in note_players.iter_mut().zip(synth_engines.iter_mut()) {
+                                player.release_all(engine.as_mut());
+                            }
                             let _ = msg_tx_out.try_send(AudioMsg::CurrentStep(0));
+                            let _ = msg_tx_out.try_send(AudioMsg::DrumStep(0));
                         }
                         AudioCmd::Record(track) => {
                             pending_record_track = Some(track);
-                            count_in_samples_remaining = samples_per_beat(seq_clock.bpm()) * count_in_beats;
+                            count_in_samples_remaining = time_signature.bar_samples(seq_clock.bpm()) as usize;
                             count_in_samples_to_next_click = 0; // first click immediately
                             count_in_click_index = 0;
                             click_samples_remaining = 0;
@@ -216,12 +319,27 @@ impl AudioEngine {
                         AudioCmd::Seek(pos) => transport.seek(pos),
                         AudioCmd::SetLevel(track, val) => {
                             if track < TRACK_COUNT {
-                                mixer.levels[track] = val;
+                                mixer.levels[track].set(val);
                             }
                         }
                         AudioCmd::SetPan(track, val) => {
                             if track < TRACK_COUNT {
-                                mixer.pans[track] = val;
+                                mixer.pans[track].set(val);
+                            }
+                        }
+                        AudioCmd::SetSend(track, aux, val) => {
+                            if track < TRACK_COUNT && aux < AUX_COUNT {
+                                mixer.sends[track][aux].set(val);
+                            }
+                        }
+                        AudioCmd::SetSendPre(track, aux, pre) => {
+                            if track < TRACK_COUNT && aux < AUX_COUNT {
+                                mixer.send_pre[track][aux] = pre;
+                            }
+                        }
+                        AudioCmd::SetChannelEq(track, band, gain) => {
+                            if track < TRACK_COUNT {
+                                channel_eqs[track].set_gain(band, gain);
                             }
                         }
                         AudioCmd::SetMute(track, val) => {
@@ -234,49 +352,211 @@ impl AudioEngine {
                                 mixer.solos[track] = val;
                             }
                         }
-                        AudioCmd::NoteOn(note, vel) => {
-                            synth_engine.note_on(note, vel);
+                        AudioCmd::NoteOn(track, note, vel) => {
+                            if track < TRACK_COUNT {
+                                if arps[track].is_enabled() {
+                                    arps[track].note_on(note, vel);
+                                } else {
+                                    synth_engines[track].note_on(note, vel);
+                                }
+                            }
                         }
-                        AudioCmd::NoteOff(note) => {
-                            synth_engine.note_off(note);
+                        AudioCmd::NoteOff(track, note) => {
+                            if track < TRACK_COUNT {
+                                if arps[track].is_enabled() {
+                                    arps[track].note_off(note);
+                                    // The key may have been pressed before the arp was switched on
+                                    if !arps[track].is_sounding(note) {
+                                        synth_engines[track].note_off(note);
+                                    }
+                                } else {
+                                    synth_engines[track].note_off(note);
+                                }
+                            }
                         }
-                        AudioCmd::SelectEngine(idx) => {
-                            synth_engine = engines::create_engine(idx);
+                        AudioCmd::SetArp(track, settings) => {
+                            if track < TRACK_COUNT {
+                                arps[track].set_settings(settings, synth_engines[track].as_mut());
+                            }
                         }
-                        AudioCmd::SetParam(idx, val) => {
-                            synth_engine.set_param(idx, val);
+                        AudioCmd::SelectEngine(track, engine) => {
+                            if track < TRACK_COUNT {
+                                synth_engine_indices[track] = engines::engine_index(engine.name()).unwrap_or(0);
+                                let old = std::mem::replace(&mut synth_engines[track], engine);
+                                let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::Engine(old)));
+                                synth_params_changed[track] = true;
+                            }
                         }
-                        AudioCmd::ToggleStep(inst, step) => {
-                            if inst < 6 && step < 16 {
-                                drum_patterns[inst][step] = !drum_patterns[inst][step];
+                        AudioCmd::SetParam(track, idx, val) => {
+                            if track < TRACK_COUNT {
+                                synth_engines[track].set_param(idx, val);
+                                synth_params_changed[track] = true;
+                            }
+                        }
+                        AudioCmd::SetDrumPattern(index, pattern) => {
+                            if let Some(slot) = pattern_bank.patterns.get_mut(index) {
+                                let old = std::mem::replace(slot, pattern);
+                                let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::DrumPattern(old)));
+                            }
+                        }
+                        AudioCmd::SetDrumStep(index, lane, step, settings) => {
+                            let lane = pattern_bank.patterns.get_mut(index).and_then(|p| p.lanes.get_mut(lane));
+                            if let Some(slot) = lane.and_then(|l| l.step_mut(step)) {
+                                *slot = settings;
+                            }
+                        }
+                        AudioCmd::ResetPattern => {
+                            queued_pattern = None;
+                            song_end = None;
+                            if current_pattern != 0 {
+                                current_pattern = 0;
+                                let _ = msg_tx_out.try_send(AudioMsg::CurrentPattern(0));
+                            }
+                        }
+                        AudioCmd::QueuePattern(index) => {
+                            if index < PATTERN_COUNT {
+                                queued_pattern = Some(index);
+                            }
+                        }
+                        AudioCmd::SetSong(chain) => {
+                            let old = std::mem::replace(&mut song, chain);
+                            let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::Song(old)));
+                            song_end = None;
+                        }
+                        AudioCmd::SetSongMode(enabled) => {
+                            song_mode = enabled;
+                            song_end = None;
+                        }
+                        AudioCmd::SetSwing(swing) => groove.set_swing(swing),
+                        AudioCmd::SetDrumMute(inst, muted) => drum_kit.set_mute(inst, muted),
+                        AudioCmd::SetQuantize(amount) => quantize = amount.clamp(0.0, 1.0),
+                        AudioCmd::TriggerDrum(inst) => {
+                            drum_kit.trigger(inst);
+                            // Played in while the tape runs: write it into the playing pattern
+                            if transport.is_playing() {
+                                let pattern = pattern_bank.patterns.get_mut(current_pattern);
+                                let written = pattern.and_then(|p| {
+                                    groove.record_hit(
+                                        p,
+                                        inst,
+                                        pattern_start,
+                                        transport.position,
+                                        seq_clock.bpm(),
+                                        quantize,
+                                    )
+                                });
+                                if let Some((step, nudge)) = written {
+                                    let _ = msg_tx_out
+                                        .try_send(AudioMsg::StepRecorded(current_pattern, inst, step, nudge));
+                                }
+                            }
+                        }
+                        AudioCmd::SetHumanize(amount) => groove.set_humanize(amount),
+                        AudioCmd::SetDrumParam(inst, idx, val) => {
+                            drum_kit.set_param(inst, idx, val);
+                        }
+                        AudioCmd::SetDrumKit(pads) => {
+                            let old = drum_kit.set_kit(pads);
+                            let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::DrumPads(old)));
+                        }
+                        AudioCmd::SetNotePattern(track, pattern) => {
+                            if let Some(player) = note_players.get_mut(track) {
+                                let old = std::mem::replace(&mut player.pattern, pattern);
+                                let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::NotePattern(old)));
                             }
                         }
                         AudioCmd::SetBpm(bpm) => {
                             seq_clock.set_bpm(bpm);
+                            drum_clock.set_bpm(bpm);
+                            song_end = None;
+                        }
+                        AudioCmd::SetTimeSignature(signature) => {
+                            time_signature = signature;
+                            last_bar = usize::MAX;
                         }
                         AudioCmd::ToggleTapeSim => {
-                            tape_sim.enabled = !tape_sim.enabled;
+                            if let Some(tape) = master_chain.iter_mut().find(|fx| fx.name() == "TAPE") {
+                                let bypassed = tape.is_bypassed();
+                                tape.set_bypass(!bypassed);
+                                master_chain_changed = true;
+                            }
                         }
                         AudioCmd::SetTapeSpeed(_speed) => {
                             // Variable speed playback (future enhancement)
                         }
+                        AudioCmd::SetEffect(track, slot, effect) => {
+                            if let Some(current) = effect_chains.get_mut(track).and_then(|c| c.get_mut(slot)) {
+                                if let Some(old) = std::mem::replace(current, effect) {
+                                    let _ = msg_tx_out.try_send(AudioMsg::Retired(Retired::Effect(old)));
+                                }
+                            }
+                        }
                         AudioCmd::ToggleEffect(track, slot) => {
-                            if track < TRACK_COUNT && slot < effect_chains[track].len() {
-                                let bypassed = effect_chains[track][slot].is_bypassed();
-                                effect_chains[track][slot].set_bypass(!bypassed);
+                            if let Some(Some(fx)) = effect_chains.get_mut(track).and_then(|c| c.get_mut(slot)) {
+                                let bypassed = fx.is_bypassed();
+                                fx.set_bypass(!bypassed);
                             }
                         }
                         AudioCmd::SetEffectParam(track, slot, param, val) => {
-                            if track < TRACK_COUNT && slot < effect_chains[track].len() {
-                                effect_chains[track][slot].set_param(param, val);
+                            if let Some(Some(fx)) = effect_chains.get_mut(track).and_then(|c| c.get_mut(slot)) {
+                                fx.set_param(param, val);
                             }
                         }
+                        AudioCmd::MoveMasterEffect(slot, dir) => {
+                            let target = if dir == 0 { slot.checked_sub(1) } else { Some(slot + 1) };
+                            if let Some(target) = target.filter(|&t| t < master_chain.len()) {
+                                if slot < master_chain.len() {
+                                    master_chain.swap(slot, target);
+                                    master_insert_gr.swap(slot, target);
+                                    master_chain_changed = true;
+                                }
+                            }
+                        }
+                        AudioCmd::ToggleMasterEffect(slot) => {
+                            if let Some(fx) = master_chain.get_mut(slot) {
+                                let bypassed = fx.is_bypassed();
+                                fx.set_bypass(!bypassed);
+                                master_chain_changed = true;
+                            }
+                        }
+                        AudioCmd::SetMasterChain(chain) => {
+                            for (slot, (insert, bypassed)) in chain.into_iter().enumerate() {
+                                let name = MASTER_INSERTS.get(insert).copied().unwrap_or_default();
+                                if let Some(from) = (slot..master_chain.len()).find(|&s| master_chain[s].name() == name) {
+                                    master_chain.swap(slot, from);
+                                    master_insert_gr.swap(slot, from);
+                                    master_chain[slot].set_bypass(bypassed);
+                                }
+                            }
+                            master_chain_changed = true;
+                        }
                         AudioCmd::SetRecordSource(src) => {
                             record_source = src;
                         }
                     }
                 }
 
+                // Report clamped/quantized values so the UI shows what the engine uses
+                for (track, changed) in synth_params_changed.iter().enumerate() {
+                    if *changed {
+                        let _ = msg_tx_out.try_send(AudioMsg::SynthParams(
+                            track,
+                            synth_engine_indices[track],
+                            engines::ParamValues::read(synth_engines[track].as_ref()),
+                        ));
+                    }
+                }
+
+                if master_chain_changed {
+                    master_chain_changed = false;
+                    let chain = std::array::from_fn(|slot| {
+                        let fx = &master_chain[slot];
+                        let insert = MASTER_INSERTS.iter().position(|name| *name == fx.name());
+                        (insert.unwrap_or(slot), fx.is_bypassed())
+                    });
+                    let _ = msg_tx_out.try_send(AudioMsg::MasterChain(chain));
+                }
+
                 // --- Drain mic input ring buffer ---
                 let mut mic_samples: Vec<f32> = Vec::new();
                 if let Ok(mut ring) = input_ring_for_output.try_lock() {
@@ -290,17 +570,40 @@ impl AudioEngine {
                 // we skip tape read/write for this callback chunk and keep live monitoring running.
                 let mut bufs_guard = buffers_out.try_lock().ok();
 
-                // --- Generate output frame by frame ---
-                for frame in data.chunks_mut(2) {
+                let frames = data.len() / 2;
+                for buf in track_left
+                    .iter_mut()
+                    .chain(track_right.iter_mut())
+                    .chain(aux_left.iter_mut())
+                    .chain(aux_right.iter_mut())
+                {
+                    buf.clear();
+                    buf.resize(frames, 0.0);
+                }
+                for buf in keys.tracks.iter_mut().chain([&mut keys.drums, &mut live_left, &mut live_right]) {
+                    buf.clear();
+                    buf.resize(frames, 0.0);
+                }
+                frame_playing.clear();
+                frame_playing.resize(frames, false);
+
+                // Tempo and timeline position at the start of the chunk, for synced effects
+                let block_start = if transport.is_playing() && transport.position < TRACK_SAMPLES {
+                    transport.position
+                } else {
+                    free_counter
+                };
+                let mut tempo = TempoInfo::new(seq_clock.bpm());
+                tempo.bar_beats = time_signature.quarter_notes();
+                tempo.beat_position = block_start as f64 / tempo.beat_samples();
+                tempo.playing = transport.is_playing() && transport.position < TRACK_SAMPLES;
+
+                // --- Generate sources frame by frame ---
+                for i in 0..frames {
                     let playing = transport.is_playing() && transport.position < TRACK_SAMPLES;
 
-                    // --- Synth output (always generates, even when not recording) ---
-                    let mut synth_buf = [0.0f32; 1];
-                    synth_engine.process(&mut synth_buf);
-                    let synth_sample = synth_buf[0];
-
-                    // --- Drum sequencer ---
-                    // Runs against tape position when playing, free-running when stopped
+                    // --- Sequencers ---
+                    // Run against tape position when playing, free-running when stopped
                     let seq_pos = if playing {
                         transport.position
                     } else {
@@ -308,16 +611,85 @@ impl AudioEngine {
                     };
                     free_counter = free_counter.wrapping_add(1);
 
-                    let (step, new_step) = seq_clock.tick(seq_pos);
+                    let (absolute_step, new_step) = seq_clock.tick(seq_pos, 4);
                     if new_step {
-                        let _ = msg_tx_out.try_send(AudioMsg::CurrentStep(step));
-                        for inst in 0..6 {
-                            if drum_patterns[inst][step] {
-                                drum_kit.trigger(inst);
-                            }
+                        let _ = msg_tx_out.try_send(AudioMsg::CurrentStep(absolute_step));
+                        for (player, engine) in note_players.iter_mut().zip(synth_engines.iter_mut()) {
+                            player.step(absolute_step, engine.as_mut());
+                        }
+                    }
+
+                    let jumped = seq_pos != last_seq_pos.wrapping_add(1);
+                    last_seq_pos = seq_pos;
+                    if seq_pos < pattern_start {
+                        // Jumped back (loop, seek): realign the pattern to the timeline
+                        pattern_start = 0;
+                        drum_clock.reset();
+                    }
+                    if jumped {
+                        if let Some(pattern) = pattern_bank.get(current_pattern) {
+                            groove.seek(pattern, pattern_start, seq_pos, seq_clock.bpm());
                         }
                     }
-                    let drum_sample = drum_kit.process();
+                    // Song mode plays each chain entry's pattern through once, laid out
+                    // along the tape; otherwise a queued pattern takes over on the next downbeat
+                    let bar = time_signature.bar(seq_pos, seq_clock.bpm());
+                    let new_bar = bar != last_bar;
+                    last_bar = bar;
+                    let next_pattern = if song_mode {
+                        if jumped || song_end.is_none_or(|end| seq_pos >= end) {
+                            let entry = pattern::song_entry(&song, &pattern_bank, seq_pos, seq_clock.bpm());
+                            song_end = entry.map(|(_, _, end)| end);
+                            entry.map(|(index, start, _)| {
+                                let _ = msg_tx_out.try_send(AudioMsg::SongEntry(index));
+                                (song[index], start)
+                            })
+                        } else {
+                            None
+                        }
+                    } else if new_bar {
+                        let bar_start = (bar as f64 * time_signature.bar_samples(seq_clock.bpm())) as usize;
+                        queued_pattern.take().map(|next| (next, bar_start))
+                    } else {
+                        None
+                    };
+                    if let Some((next, start)) = next_pattern {
+                        pattern_start = start.min(seq_pos);
+                        drum_clock.reset();
+                        if next != current_pattern {
+                            current_pattern = next;
+                            let _ = msg_tx_out.try_send(AudioMsg::CurrentPattern(next));
+                        }
+                        if let Some(pattern) = pattern_bank.get(current_pattern) {
+                            groove.restart(pattern, pattern_start, seq_pos, seq_clock.bpm());
+                        }
+                    }
+
+                    if let Some(pattern) = pattern_bank.get(current_pattern) {
+                        let (drum_step, new_drum_step) =
+                            drum_clock.tick(seq_pos - pattern_start, pattern.resolution.steps_per_beat());
+                        if new_drum_step {
+                            let _ = msg_tx_out.try_send(AudioMsg::DrumStep(drum_step));
+                        }
+                        groove.schedule(pattern, pattern_start, seq_pos, seq_clock.bpm());
+                    }
+                    groove.fire(seq_pos, &mut drum_kit);
+                    for (arp, engine) in arps.iter_mut().zip(synth_engines.iter_mut()) {
+                        arp.process(seq_pos, seq_clock.bpm(), engine.as_mut());
+                    }
+
+                    // --- Synth output (always generates, even when not recording) ---
+                    // Engines add into the buffer, so this sums every track's instrument
+                    let mut synth_buf = [0.0f32; 1];
+                    for engine in &mut synth_engines {
+                        engine.process(&mut synth_buf);
+                    }
+                    let synth_sample = synth_buf[0];
+
+                    let (drum_left, drum_right) = drum_kit.process();
+                    // Mono drum mix for recording to a track
+                    let drum_sample = (drum_left + drum_right) * 0.5;
+                    keys.drums[i] = drum_sample;
 
                     // --- Record count-in ---
                     if let Some(track) = pending_record_track {
@@ -325,18 +697,21 @@ impl AudioEngine {
                             // Start recording at loop start for tighter overdubs.
                             transport.seek(0);
                             seq_clock.reset();
+                            drum_clock.reset();
+                            pattern_start = 0;
                             let _ = msg_tx_out.try_send(AudioMsg::CurrentStep(0));
                             transport.record(track);
                             pending_record_track = None;
                         } else {
                             if count_in_samples_to_next_click == 0 {
-                                let accented = count_in_click_index % count_in_beats == 0;
+                                let accented = count_in_click_index.is_multiple_of(time_signature.beats as usize);
                                 click_freq = if accented { 1900.0 } else { 1500.0 };
                                 click_amp = if accented { 0.32 } else { 0.22 };
                                 click_phase = 0.0;
                                 click_samples_remaining = click_len_samples;
                                 count_in_click_index = count_in_click_index.wrapping_add(1);
-                                count_in_samples_to_next_click = samples_per_beat(seq_clock.bpm());
+                                count_in_samples_to_next_click =
+                                    (time_signature.beat_samples(seq_clock.bpm()) as usize).max(1);
                             }
                             count_in_samples_to_next_click =
                                 count_in_samples_to_next_click.saturating_sub(1);
@@ -361,11 +736,11 @@ impl AudioEngine {
                             let mut rec_sample = 0.0f32;
 
                             // Mic input
-                            if matches!(record_source, RecordSource::Mic | RecordSource::All) {
-                                if mic_read_pos < mic_samples.len() {
-                                    rec_sample += mic_samples[mic_read_pos];
-                                    mic_read_pos += 1;
-                                }
+                            if matches!(record_source, RecordSource::Mic | RecordSource::All)
+                                && mic_read_pos < mic_samples.len()
+                            {
+                                rec_sample += mic_samples[mic_read_pos];
+                                mic_read_pos += 1;
                             }
 
                             // Synth output
@@ -384,6 +759,11 @@ impl AudioEngine {
                                 rec_sample += drum_sample;
                             }
 
+                            // A single drum lane
+                            if let RecordSource::DrumLane(lane) = record_source {
+                                rec_sample += drum_kit.lane_output(lane);
+                            }
+
                             if let Some(bufs) = bufs_guard.as_mut() {
                                 bufs.tracks[rec_track].data[transport.position] = rec_sample;
                                 let current_len = bufs.tracks[rec_track]
@@ -398,32 +778,20 @@ impl AudioEngine {
                         }
                     }
 
-                    // --- Read track data + apply per-track effects ---
+                    // --- Read track data ---
+                    frame_playing[i] = playing;
                     if playing {
-                        let mut track_samples = [0.0f32; TRACK_COUNT];
-                        for t in 0..TRACK_COUNT {
-                            let mut sample = if let Some(bufs) = bufs_guard.as_ref() {
-                                bufs.tracks[t].read(transport.position)
-                            } else {
-                                0.0
-                            };
-
-                            // Apply per-track effects
-                            if !effect_chains[t].is_empty() {
-                                let mut buf = [sample];
-                                for fx in &mut effect_chains[t] {
-                                    if !fx.is_bypassed() {
-                                        fx.process(&mut buf);
-                                    }
-                                }
-                                sample = buf[0];
+                        if let Some(bufs) = bufs_guard.as_ref() {
+                            for t in 0..TRACK_COUNT {
+                                let sample = bufs.tracks[t].read(transport.position);
+                                track_left[t][i] = sample;
+                                track_right[t][i] = sample;
+                                keys.tracks[t][i] = sample;
                             }
-
-                            track_samples[t] = sample;
-                            track_meters[t].push(sample);
                         }
 
-                        let (mut left, mut right) = mixer.mix(&track_samples);
+                        let mut left = 0.0f32;
+                        let mut right = 0.0f32;
 
                         // Avoid doubling/echo: when a source is actively being recorded,
                         // don't also add a parallel live monitor path for that same source.
@@ -445,33 +813,92 @@ impl AudioEngine {
                         }
 
                         if monitor_drum {
-                            left += drum_sample * 0.5;
-                            right += drum_sample * 0.5;
+                            let (mut drum_l, mut drum_r) = (drum_left, drum_right);
+                            // A lane being recorded on its own is heard from the tape instead
+                            if let (true, RecordSource::DrumLane(lane)) = (recording, record_source) {
+                                let (lane_l, lane_r) = drum_kit.lane_stereo(lane);
+                                drum_l -= lane_l;
+                                drum_r -= lane_r;
+                            }
+                            left += drum_l * 0.5;
+                            right += drum_r * 0.5;
                         }
 
                         // Count-in click monitor
-                        left += metronome_sample;
-                        right += metronome_sample;
-
-                        // Tape simulation
-                        tape_sim.process_stereo(&mut left, &mut right);
-
-                        frame[0] = left.clamp(-1.0, 1.0);
-                        frame[1] = right.clamp(-1.0, 1.0);
-
-                        master_meter_l.push(left);
-                        master_meter_r.push(right);
+                        live_left[i] = left + metronome_sample;
+                        live_right[i] = right + metronome_sample;
 
                         transport.advance();
                     } else {
                         // When stopped, still output synth + drums for live preview
-                        let left = (synth_sample + drum_sample) * 0.5 + metronome_sample;
-                        let right = (synth_sample + drum_sample) * 0.5 + metronome_sample;
-                        frame[0] = left.clamp(-1.0, 1.0);
-                        frame[1] = right.clamp(-1.0, 1.0);
-                        master_meter_l.push(left);
-                        master_meter_r.push(right);
+                        live_left[i] = (synth_sample + drum_left) * 0.5 + metronome_sample;
+                        live_right[i] = (synth_sample + drum_right) * 0.5 + metronome_sample;
+                    }
+                }
+
+                // --- Apply per-track effects to the whole chunk ---
+                if frame_playing.iter().any(|&p| p) {
+                    for t in 0..TRACK_COUNT {
+                        channel_eqs[t].process(&mut track_left[t], &mut track_right[t]);
+                        process_chain(effect_chains[t].iter_mut().flatten(), &mut track_left[t], &mut track_right[t], &keys, &tempo);
                     }
+                }
+
+                // --- Mix tracks and fill the aux buses ---
+                for i in 0..frames {
+                    mixer.tick();
+                    if !frame_playing[i] {
+                        continue;
+                    }
+                    let mut samples_l = [0.0f32; TRACK_COUNT];
+                    let mut samples_r = [0.0f32; TRACK_COUNT];
+                    for t in 0..TRACK_COUNT {
+                        samples_l[t] = track_left[t][i];
+                        samples_r[t] = track_right[t][i];
+                        track_meters[t].push((samples_l[t] + samples_r[t]) * 0.5);
+                    }
+
+                    let (mix_l, mix_r) = mixer.mix(&samples_l, &samples_r);
+                    live_left[i] += mix_l;
+                    live_right[i] += mix_r;
+
+                    for (aux, (send_l, send_r)) in mixer.aux_sends(&samples_l, &samples_r).into_iter().enumerate() {
+                        aux_left[aux][i] = send_l;
+                        aux_right[aux][i] = send_r;
+                    }
+                }
+
+                // --- Aux returns: run every callback so reverb and delay tails
+                // ring out after the transport stops ---
+                for aux in 0..AUX_COUNT {
+                    process_chain(&mut aux_chains[aux], &mut aux_left[aux], &mut aux_right[aux], &keys, &tempo);
+                }
+
+                // --- Master bus: aux returns, inserts, limiter ---
+                for i in 0..frames {
+                    for aux in 0..AUX_COUNT {
+                        live_left[i] += aux_left[aux][i];
+                        live_right[i] += aux_right[aux][i];
+                    }
+                }
+                process_chain(&mut master_chain, &mut live_left, &mut live_right, &keys, &tempo);
+                for (gr, fx) in master_insert_gr.iter_mut().zip(&master_chain) {
+                    if !fx.is_bypassed() {
+                        *gr = gr.max(fx.gain_reduction());
+                    }
+                }
+                limiter.process(&mut live_left, &mut live_right);
+                master_gr = master_gr.max(limiter.gain_reduction());
+
+                // --- Output frame by frame ---
+                for (i, frame) in data.chunks_mut(2).enumerate() {
+                    let left = live_left[i];
+                    let right = live_right[i];
+                    frame[0] = left;
+                    frame[1] = right;
+
+                    master_meter_l.push(left);
+                    master_meter_r.push(right);
 
                     report_counter += 1;
                     if report_counter >= report_interval {
@@ -495,6 +922,10 @@ impl AudioEngine {
                             master_meter_l.take_rms(),
                             master_meter_r.take_rms(),
                         ));
+                        let _ = msg_tx_out.try_send(AudioMsg::MasterGainReduction(master_gr));
+                        master_gr = 0.0;
+                        let _ = msg_tx_out.try_send(AudioMsg::InsertGainReduction(master_insert_gr));
+                        master_insert_gr.iter_mut().for_each(|gr| *gr = 0.0);
                     }
                 }
             },
@@ -1,49 +1,139 @@
-use crate::constants::TRACK_COUNT;
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::{AUX_COUNT, TRACK_COUNT};
 
+/// Track levels, pans and sends ramp to new values so fader moves, mutes and
+/// solos don't click; call `tick` once per mixed frame.
 pub struct MixerState {
-    pub levels: [f32; TRACK_COUNT],
-    pub pans: [f32; TRACK_COUNT],
+    pub levels: [SmoothedParam; TRACK_COUNT],
+    pub pans: [SmoothedParam; TRACK_COUNT],
     pub mutes: [bool; TRACK_COUNT],
     pub solos: [bool; TRACK_COUNT],
+    /// Send level per track into each aux bus
+    pub sends: [[SmoothedParam; AUX_COUNT]; TRACK_COUNT],
+    /// Whether a send taps the track before its fader
+    pub send_pre: [[bool; AUX_COUNT]; TRACK_COUNT],
+    // 1.0 while a track is heard, 0.0 when muted or soloed out
+    audible: [SmoothedParam; TRACK_COUNT],
 }
 
 impl MixerState {
     pub fn new() -> Self {
         Self {
-            levels: [0.8; TRACK_COUNT],
-            pans: [0.0; TRACK_COUNT],
+            levels: [SmoothedParam::new(0.8); TRACK_COUNT],
+            pans: [SmoothedParam::new(0.0); TRACK_COUNT],
             mutes: [false; TRACK_COUNT],
             solos: [false; TRACK_COUNT],
+            sends: [[SmoothedParam::new(0.0); AUX_COUNT]; TRACK_COUNT],
+            send_pre: [[false; AUX_COUNT]; TRACK_COUNT],
+            audible: [SmoothedParam::new(1.0); TRACK_COUNT],
         }
     }
 
-    /// Returns (left_gain, right_gain) for a given track
-    pub fn track_gain(&self, track: usize) -> (f32, f32) {
+    /// Whether a track is heard, given mutes and solos
+    fn audible(&self, track: usize) -> bool {
         if self.mutes[track] {
-            return (0.0, 0.0);
+            return false;
         }
-
         let any_solo = self.solos.iter().any(|&s| s);
-        if any_solo && !self.solos[track] {
-            return (0.0, 0.0);
+        !any_solo || self.solos[track]
+    }
+
+    /// Advance every ramp by one frame
+    pub fn tick(&mut self) {
+        for track in 0..TRACK_COUNT {
+            let audible = if self.audible(track) { 1.0 } else { 0.0 };
+            self.audible[track].set(audible);
+            self.audible[track].tick();
+            self.levels[track].tick();
+            self.pans[track].tick();
+            for send in &mut self.sends[track] {
+                send.tick();
+            }
         }
+    }
 
-        let level = self.levels[track];
-        let pan = self.pans[track]; // -1.0 to 1.0
+    /// Returns (left_gain, right_gain) for a given track
+    pub fn track_gain(&self, track: usize) -> (f32, f32) {
+        let level = self.levels[track].value() * self.audible[track].value();
+        let pan = self.pans[track].value(); // -1.0 to 1.0
         let left = level * (1.0 - pan.max(0.0));
         let right = level * (1.0 + pan.min(0.0));
         (left, right)
     }
 
-    /// Mix track samples into stereo output
-    pub fn mix(&self, track_samples: &[f32; TRACK_COUNT]) -> (f32, f32) {
+    /// Mix stereo track samples into stereo output; pan balances each track's two sides
+    pub fn mix(&self, track_left: &[f32; TRACK_COUNT], track_right: &[f32; TRACK_COUNT]) -> (f32, f32) {
         let mut left = 0.0f32;
         let mut right = 0.0f32;
         for i in 0..TRACK_COUNT {
             let (gl, gr) = self.track_gain(i);
-            left += track_samples[i] * gl;
-            right += track_samples[i] * gr;
+            left += track_left[i] * gl;
+            right += track_right[i] * gr;
         }
         (left, right)
     }
+
+    /// Gain of a track's send into an aux bus. Post-fader sends follow the
+    /// fader; both kinds follow mute and solo.
+    pub fn send_gain(&self, track: usize, aux: usize) -> f32 {
+        let send = self.sends[track][aux].value() * self.audible[track].value();
+        if self.send_pre[track][aux] {
+            send
+        } else {
+            send * self.levels[track].value()
+        }
+    }
+
+    /// Sum stereo track samples into each aux bus
+    pub fn aux_sends(
+        &self,
+        track_left: &[f32; TRACK_COUNT],
+        track_right: &[f32; TRACK_COUNT],
+    ) -> [(f32, f32); AUX_COUNT] {
+        std::array::from_fn(|aux| {
+            let mut left = 0.0f32;
+            let mut right = 0.0f32;
+            for i in 0..TRACK_COUNT {
+                let gain = self.send_gain(i, aux);
+                left += track_left[i] * gain;
+                right += track_right[i] * gain;
+            }
+            (left, right)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::smoothing::SMOOTHING_MS;
+    use crate::constants::SAMPLE_RATE;
+
+    #[test]
+    fn fader_and_mute_changes_ramp_instead_of_jumping() {
+        let ramp = (SMOOTHING_MS * 0.001 * SAMPLE_RATE as f32) as usize;
+        let mut mixer = MixerState::new();
+        let ramp_gains = |mixer: &mut MixerState| -> Vec<f32> {
+            (0..=ramp)
+                .map(|_| {
+                    mixer.tick();
+                    mixer.track_gain(0).0
+                })
+                .collect()
+        };
+
+        // Fader from 0.8 down to 0.2, in small steps
+        mixer.levels[0].set(0.2);
+        let gains = ramp_gains(&mut mixer);
+        assert!(gains.windows(2).all(|w| w[1] <= w[0] && w[0] - w[1] <= 0.6 / ramp as f32 + 1e-6));
+        assert!(gains[0] > 0.2);
+        assert_eq!(gains[ramp], 0.2);
+
+        // Muting fades the track out rather than cutting it
+        mixer.mutes[0] = true;
+        let gains = ramp_gains(&mut mixer);
+        assert!(gains[0] > 0.19);
+        assert_eq!(gains[ramp], 0.0);
+        assert_eq!(mixer.track_gain(1).0, 0.8);
+    }
 }
@@ -3,3 +3,4 @@ pub mod transport;
 pub mod engine;
 pub mod mixer;
 pub mod recorder;
+pub mod smoothing;
@@ -0,0 +1,94 @@
+use crate::constants::SAMPLE_RATE;
+
+/// Ramp time for parameter changes: long enough to avoid zipper noise on
+/// stepped edits, short enough to feel immediate
+pub const SMOOTHING_MS: f32 = 5.0;
+
+/// A parameter that ramps linearly to each new target instead of jumping.
+/// Call `tick` once per sample in the audio thread to advance it.
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    step: f32,
+    remaining: usize,
+    ramp: usize,
+}
+
+impl SmoothedParam {
+    pub fn new(value: f32) -> Self {
+        Self::with_ramp(value, SMOOTHING_MS)
+    }
+
+    pub fn with_ramp(value: f32, ms: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            step: 0.0,
+            remaining: 0,
+            ramp: ((ms * 0.001 * SAMPLE_RATE as f32) as usize).max(1),
+        }
+    }
+
+    /// Start ramping toward a new value
+    pub fn set(&mut self, target: f32) {
+        if target == self.target {
+            return;
+        }
+        self.target = target;
+        self.remaining = self.ramp;
+        self.step = (target - self.current) / self.ramp as f32;
+    }
+
+    /// Jump straight to a value, e.g. when an effect or voice is first set up
+    pub fn set_immediate(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.remaining = 0;
+    }
+
+    /// Finish any ramp at once, jumping to the target
+    pub fn settle(&mut self) {
+        self.set_immediate(self.target);
+    }
+
+    /// Value being ramped toward; this is what the parameter reads back as
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    pub fn is_smoothing(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Current value, then advance one sample
+    pub fn tick(&mut self) -> f32 {
+        let value = self.current;
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.current = if self.remaining == 0 { self.target } else { self.current + self.step };
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_to_the_target_over_the_smoothing_time() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set(1.0);
+        let ramp = (SMOOTHING_MS * 0.001 * SAMPLE_RATE as f32) as usize;
+
+        let values: Vec<f32> = (0..ramp + 2).map(|_| param.tick()).collect();
+        assert!(values.windows(2).all(|w| w[1] >= w[0] && w[1] - w[0] <= 1.0 / ramp as f32 + 1e-6));
+        assert_eq!(values[ramp], 1.0);
+        assert!(!param.is_smoothing());
+    }
+}
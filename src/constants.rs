@@ -1,5 +1,7 @@
 pub const SAMPLE_RATE: u32 = 44_100;
 pub const BUFFER_SIZE: usize = 512;
+/// Largest audio callback block that block buffers are preallocated for, in frames
+pub const MAX_BLOCK_FRAMES: usize = 8192;
 pub const TRACK_COUNT: usize = 4;
 /// Maximum recording duration in seconds
 pub const MAX_DURATION_SECS: usize = 360;
@@ -9,3 +11,20 @@ pub const TRACK_SAMPLES: usize = SAMPLE_RATE as usize * MAX_DURATION_SECS;
 pub const UI_FPS: u64 = 60;
 /// Channel capacity for inter-thread messages
 pub const CHANNEL_CAPACITY: usize = 1024;
+/// Directory the sampler loads WAV files from
+pub const SAMPLES_DIR: &str = "samples";
+/// Longest song chain, in entries
+pub const MAX_SONG_LENGTH: usize = 64;
+/// Directory of drum kits, one subdirectory with a `kit.json` each
+pub const KITS_DIR: &str = "kits";
+/// Longest region the sampler copies from a track
+pub const MAX_SAMPLE_SECS: usize = 10;
+/// Aux send/return buses in the mixer
+pub const AUX_COUNT: usize = 2;
+/// Labels of the aux buses, after the effect each one hosts
+pub const AUX_NAMES: [&str; AUX_COUNT] = ["REV", "DLY"];
+/// Insert effect slots per track
+pub const FX_SLOTS: usize = 3;
+/// Master bus inserts, in their starting order; the limiter always follows them
+pub const MASTER_INSERT_COUNT: usize = 4;
+pub const MASTER_INSERTS: [&str; MASTER_INSERT_COUNT] = ["GATE", "EQ", "COMP", "TAPE"];
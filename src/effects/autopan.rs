@@ -0,0 +1,68 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::tempo::{self, SyncedLfo, TempoInfo, BAR_CYCLES};
+use crate::effects::{self, Effect};
+
+/// Sweeps the signal between the speakers with an equal-power pan law
+pub struct AutoPan {
+    lfo: SyncedLfo,
+    depth: SmoothedParam,
+    bypassed: bool,
+}
+
+impl AutoPan {
+    pub fn new() -> Self {
+        Self {
+            lfo: SyncedLfo::new(0.5),
+            depth: SmoothedParam::new(0.8),
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 3] = [(0.05, 10.0), (0.0, 1.0), (0.0, (BAR_CYCLES.len() - 1) as f32)];
+
+impl Effect for AutoPan {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let pan = (self.lfo.tick() * std::f64::consts::TAU).sin() as f32 * self.depth.tick();
+            // Centre is unity on both sides
+            let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+            *l *= angle.cos() * std::f32::consts::SQRT_2;
+            *r *= angle.sin() * std::f32::consts::SQRT_2;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.lfo.set_rate(value),
+            1 => self.depth.set(value),
+            2 => self.lfo.sync = tempo::sync_index(value, BAR_CYCLES.len()),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.lfo.rate(),
+            1 => self.depth.target(),
+            2 => self.lfo.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "RATE", 1 => "DEPTH", 2 => "SYNC", _ => "" }
+    }
+    fn name(&self) -> &str { "AUTOPAN" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.lfo.set_transport(tempo); }
+    fn settle(&mut self) {
+        self.depth.settle();
+    }
+}
@@ -0,0 +1,85 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::{self, Effect};
+
+/// Bit-depth and sample-rate reducer
+pub struct Bitcrusher {
+    bits: SmoothedParam,
+    downsample: f32,
+    mix: SmoothedParam,
+    // Held sample per channel and samples left until the next grab
+    held: [f32; 2],
+    counter: f32,
+    bypassed: bool,
+}
+
+impl Bitcrusher {
+    pub fn new() -> Self {
+        Self {
+            bits: SmoothedParam::new(8.0),
+            downsample: 4.0,
+            mix: SmoothedParam::new(1.0),
+            held: [0.0; 2],
+            counter: 0.0,
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 3] = [(1.0, 16.0), (1.0, 32.0), (0.0, 1.0)];
+
+impl Effect for Bitcrusher {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let mut levels = 2f32.powf(self.bits.value() - 1.0);
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mix = self.mix.tick();
+            // Only recompute the step size while the depth is gliding
+            if self.bits.is_smoothing() {
+                self.bits.tick();
+                levels = 2f32.powf(self.bits.value() - 1.0);
+            }
+            // Grab a new sample every `downsample` frames and hold it in between
+            self.counter -= 1.0;
+            if self.counter <= 0.0 {
+                self.counter += self.downsample;
+                self.held = [*l, *r].map(|x| (x * levels).round() / levels);
+            }
+            *l = *l * (1.0 - mix) + self.held[0] * mix;
+            *r = *r * (1.0 - mix) + self.held[1] * mix;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.bits.set(value),
+            1 => self.downsample = value,
+            2 => self.mix.set(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.bits.target(),
+            1 => self.downsample,
+            2 => self.mix.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "BITS", 1 => "DOWNSMP", 2 => "MIX", _ => "" }
+    }
+    fn name(&self) -> &str { "CRUSH" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) {
+        self.bits.settle();
+        self.mix.settle();
+    }
+}
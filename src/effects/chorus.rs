@@ -1,72 +1,104 @@
+use crate::audio::smoothing::SmoothedParam;
 use crate::constants::SAMPLE_RATE;
-use crate::effects::Effect;
+use crate::effects::tempo::{self, SyncedLfo, TempoInfo, BAR_CYCLES};
+use crate::effects::{self, Effect};
 
 pub struct Chorus {
-    buffer: Vec<f32>,
+    buffer_l: Vec<f32>,
+    buffer_r: Vec<f32>,
     write_pos: usize,
-    lfo_phase: f64,
-    rate: f32,
-    depth: f32,
-    mix: f32,
+    lfo: SyncedLfo,
+    depth: SmoothedParam,
+    mix: SmoothedParam,
     bypassed: bool,
 }
 
 impl Chorus {
     pub fn new() -> Self {
         Self {
-            buffer: vec![0.0; SAMPLE_RATE as usize],
+            buffer_l: vec![0.0; SAMPLE_RATE as usize],
+            buffer_r: vec![0.0; SAMPLE_RATE as usize],
             write_pos: 0,
-            lfo_phase: 0.0,
-            rate: 0.5,
-            depth: 0.003,
-            mix: 0.5,
+            lfo: SyncedLfo::new(0.5),
+            depth: SmoothedParam::new(0.003),
+            mix: SmoothedParam::new(0.5),
             bypassed: false,
         }
     }
 }
 
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 4] = [
+    (0.1, 5.0),
+    (0.001, 0.02),
+    (0.0, 1.0),
+    (0.0, (BAR_CYCLES.len() - 1) as f32),
+];
+
 impl Effect for Chorus {
-    fn process(&mut self, input: &mut [f32]) {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
         let sr = SAMPLE_RATE as f64;
-        let buf_len = self.buffer.len();
-
-        for sample in input.iter_mut() {
-            let dry = *sample;
+        let buf_len = self.buffer_l.len();
 
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mix = self.mix.tick();
+            let depth = self.depth.tick() as f64;
             // Write to buffer
-            self.buffer[self.write_pos] = *sample;
+            self.buffer_l[self.write_pos] = *l;
+            self.buffer_r[self.write_pos] = *r;
 
-            // LFO modulates delay time
-            let lfo = (self.lfo_phase * std::f64::consts::TAU).sin();
-            let delay_samples = (self.depth as f64 * sr) * (1.0 + lfo) * 0.5;
-            let delay_samples = delay_samples.max(1.0) as usize;
+            // LFO modulates delay time; the right side runs in antiphase for width
+            let lfo = (self.lfo.tick() * std::f64::consts::TAU).sin();
+            let delay_at = |lfo: f64| {
+                let delay_samples = (depth * sr) * (1.0 + lfo) * 0.5;
+                let delay_samples = delay_samples.max(1.0) as usize;
+                (self.write_pos + buf_len - delay_samples) % buf_len
+            };
 
             // Read from buffer with delay
-            let read_pos = (self.write_pos + buf_len - delay_samples) % buf_len;
-            let delayed = self.buffer[read_pos];
+            let delayed_l = self.buffer_l[delay_at(lfo)];
+            let delayed_r = self.buffer_r[delay_at(-lfo)];
 
             self.write_pos = (self.write_pos + 1) % buf_len;
-            self.lfo_phase += self.rate as f64 / sr;
-            if self.lfo_phase >= 1.0 { self.lfo_phase -= 1.0; }
 
-            *sample = dry * (1.0 - self.mix) + delayed * self.mix;
+            *l = *l * (1.0 - mix) + delayed_l * mix;
+            *r = *r * (1.0 - mix) + delayed_r * mix;
         }
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
         match index {
-            0 => self.rate = value.clamp(0.1, 5.0),
-            1 => self.depth = value.clamp(0.001, 0.02),
-            2 => self.mix = value.clamp(0.0, 1.0),
+            0 => self.lfo.set_rate(value),
+            1 => self.depth.set(value),
+            2 => self.mix.set(value),
+            3 => self.lfo.sync = tempo::sync_index(value, BAR_CYCLES.len()),
             _ => {}
         }
     }
 
-    fn param_count(&self) -> usize { 3 }
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.lfo.rate(),
+            1 => self.depth.target(),
+            2 => self.mix.target(),
+            3 => self.lfo.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
     fn param_name(&self, index: usize) -> &str {
-        match index { 0 => "RATE", 1 => "DEPTH", 2 => "MIX", _ => "" }
+        match index { 0 => "RATE", 1 => "DEPTH", 2 => "MIX", 3 => "SYNC", _ => "" }
     }
     fn name(&self) -> &str { "CHORUS" }
     fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
     fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.lfo.set_transport(tempo); }
+    fn settle(&mut self) {
+        self.depth.settle();
+        self.mix.settle();
+    }
 }
@@ -0,0 +1,161 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::{MAX_BLOCK_FRAMES, TRACK_COUNT};
+use crate::effects::{self, copy_key, detector_level, time_coef, to_db, Effect, Sidechain};
+
+/// Feed-forward compressor with a soft knee, optionally keyed from a sidechain
+pub struct Compressor {
+    threshold: SmoothedParam, // dB
+    ratio: SmoothedParam,
+    attack: f32,  // ms
+    release: f32, // ms
+    makeup: SmoothedParam, // dB
+    knee: SmoothedParam,   // dB
+    sidechain: Option<Sidechain>,
+    key: Vec<f32>,
+    // Current gain change in dB (zero or negative)
+    gain: f32,
+    reduction: f32,
+    bypassed: bool,
+}
+
+impl Compressor {
+    pub fn new() -> Self {
+        Self {
+            threshold: SmoothedParam::new(-18.0),
+            ratio: SmoothedParam::new(4.0),
+            attack: 10.0,
+            release: 120.0,
+            makeup: SmoothedParam::new(0.0),
+            knee: SmoothedParam::new(6.0),
+            sidechain: None,
+            key: Vec::with_capacity(MAX_BLOCK_FRAMES),
+            gain: 0.0,
+            reduction: 0.0,
+            bypassed: false,
+        }
+    }
+
+    /// Static gain change in dB for a detector level in dB
+    fn curve(level: f32, threshold: f32, ratio: f32, knee: f32) -> f32 {
+        let over = level - threshold;
+        let slope = 1.0 / ratio - 1.0;
+        if 2.0 * over <= -knee {
+            0.0
+        } else if 2.0 * over.abs() < knee {
+            slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 7] = [
+    (-60.0, 0.0),
+    (1.0, 20.0),
+    (0.1, 100.0),
+    (10.0, 1000.0),
+    (0.0, 24.0),
+    (0.0, 12.0),
+    (0.0, (TRACK_COUNT + 1) as f32),
+];
+
+impl Effect for Compressor {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let attack = time_coef(self.attack);
+        let release = time_coef(self.release);
+        let key = self.sidechain.map(|_| self.key.as_slice());
+        self.reduction = 0.0;
+
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let level = to_db(detector_level(key, i, *l, *r));
+            let target = Self::curve(level, self.threshold.tick(), self.ratio.tick(), self.knee.tick());
+            let coef = if target < self.gain { attack } else { release };
+            self.gain = target + (self.gain - target) * coef;
+
+            let gain = 10f32.powf((self.gain + self.makeup.tick()) / 20.0);
+            *l *= gain;
+            *r *= gain;
+            self.reduction = self.reduction.max(-self.gain);
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.threshold.set(value),
+            1 => self.ratio.set(value),
+            2 => self.attack = value,
+            3 => self.release = value,
+            4 => self.makeup.set(value),
+            5 => self.knee.set(value),
+            6 => self.sidechain = Sidechain::from_param(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.threshold.target(),
+            1 => self.ratio.target(),
+            2 => self.attack,
+            3 => self.release,
+            4 => self.makeup.target(),
+            5 => self.knee.target(),
+            6 => Sidechain::to_param(self.sidechain),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index {
+            0 => "THRESH",
+            1 => "RATIO",
+            2 => "ATTACK",
+            3 => "RELEASE",
+            4 => "MAKEUP",
+            5 => "KNEE",
+            6 => "KEY",
+            _ => "",
+        }
+    }
+    fn name(&self) -> &str { "COMP" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn gain_reduction(&self) -> f32 { self.reduction }
+    fn sidechain(&self) -> Option<Sidechain> { self.sidechain }
+    fn set_key(&mut self, key: &[f32]) { copy_key(&mut self.key, key); }
+    fn settle(&mut self) {
+        for param in [&mut self.threshold, &mut self.ratio, &mut self.makeup, &mut self.knee] {
+            param.settle();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidechain_key_ducks_the_input() {
+        let mut comp = Compressor::new();
+        comp.set_param(2, 0.1);
+        comp.set_param(6, 5.0);
+
+        let mut left = vec![0.1; 512];
+        let mut right = vec![0.1; 512];
+        // Silent key: the quiet input passes untouched
+        comp.set_key(&[0.0; 512]);
+        comp.process(&mut left, &mut right);
+        assert!((left[511] - 0.1).abs() < 1e-4);
+
+        // A loud key pulls the same input down
+        comp.set_key(&[1.0; 512]);
+        comp.process(&mut left, &mut right);
+        assert!(left[511] < 0.05);
+        assert!(comp.gain_reduction() > 10.0);
+    }
+}
@@ -1,13 +1,24 @@
-use crate::effects::Effect;
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::tempo::{self, TempoInfo, NOTE_DIVISIONS};
+use crate::effects::{self, read_frac, Effect};
 use crate::constants::SAMPLE_RATE;
 
+/// Time changes glide over this long, bending the repeats' pitch like a tape
+/// delay instead of jumping the read position
+const TIME_RAMP_MS: f32 = 50.0;
+
 pub struct Delay {
     buffer_l: Vec<f32>,
     buffer_r: Vec<f32>,
     write_pos: usize,
     time: f32,      // in seconds
-    feedback: f32,
-    mix: f32,
+    // Note division index; 0 uses `time` instead
+    sync: usize,
+    beat_samples: f64,
+    // Delay in samples, gliding to the time or synced division
+    length: SmoothedParam,
+    feedback: SmoothedParam,
+    mix: SmoothedParam,
     bypassed: bool,
 }
 
@@ -19,43 +30,132 @@ impl Delay {
             buffer_r: vec![0.0; max_samples],
             write_pos: 0,
             time: 0.375,
-            feedback: 0.4,
-            mix: 0.3,
+            sync: 0,
+            beat_samples: TempoInfo::new(120.0).beat_samples(),
+            length: SmoothedParam::with_ramp(0.375 * SAMPLE_RATE as f32, TIME_RAMP_MS),
+            feedback: SmoothedParam::new(0.4),
+            mix: SmoothedParam::new(0.3),
             bypassed: false,
         }
     }
+
+    /// Delay in samples for the current time or synced division
+    fn target_length(&self) -> f32 {
+        let samples = match NOTE_DIVISIONS[self.sync].1 {
+            beats if beats > 0.0 => (beats * self.beat_samples) as f32,
+            _ => self.time * SAMPLE_RATE as f32,
+        };
+        samples.clamp(1.0, (self.buffer_l.len() - 2) as f32)
+    }
 }
 
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 4] = [
+    (0.01, 2.0),
+    (0.0, 0.9),
+    (0.0, 1.0),
+    (0.0, (NOTE_DIVISIONS.len() - 1) as f32),
+];
+
 impl Effect for Delay {
-    fn process(&mut self, input: &mut [f32]) {
-        let delay_samples = (self.time * SAMPLE_RATE as f32) as usize;
-        let delay_samples = delay_samples.min(self.buffer_l.len() - 1).max(1);
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.length.set(self.target_length());
 
-        for sample in input.iter_mut() {
-            let read_pos = (self.write_pos + self.buffer_l.len() - delay_samples) % self.buffer_l.len();
-            let delayed = self.buffer_l[read_pos];
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let length = self.length.tick();
+            let delayed_l = read_frac(&self.buffer_l, self.write_pos, length);
+            let delayed_r = read_frac(&self.buffer_r, self.write_pos, length);
+            let feedback = self.feedback.tick();
+            let mix = self.mix.tick();
 
-            self.buffer_l[self.write_pos] = *sample + delayed * self.feedback;
+            // Ping-pong: the input enters on the left, and each repeat crosses
+            // to the other side
+            self.buffer_l[self.write_pos] = (*l + *r) * 0.5 + delayed_r * feedback;
+            self.buffer_r[self.write_pos] = delayed_l * feedback;
             self.write_pos = (self.write_pos + 1) % self.buffer_l.len();
 
-            *sample = *sample * (1.0 - self.mix) + delayed * self.mix;
+            *l = *l * (1.0 - mix) + delayed_l * mix;
+            *r = *r * (1.0 - mix) + delayed_r * mix;
         }
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
         match index {
-            0 => self.time = value.clamp(0.01, 2.0),
-            1 => self.feedback = value.clamp(0.0, 0.9),
-            2 => self.mix = value.clamp(0.0, 1.0),
+            0 => self.time = value,
+            1 => self.feedback.set(value),
+            2 => self.mix.set(value),
+            3 => self.sync = tempo::sync_index(value, NOTE_DIVISIONS.len()),
             _ => {}
         }
     }
 
-    fn param_count(&self) -> usize { 3 }
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.time,
+            1 => self.feedback.target(),
+            2 => self.mix.target(),
+            3 => self.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
     fn param_name(&self, index: usize) -> &str {
-        match index { 0 => "TIME", 1 => "FDBK", 2 => "MIX", _ => "" }
+        match index { 0 => "TIME", 1 => "FDBK", 2 => "MIX", 3 => "SYNC", _ => "" }
     }
     fn name(&self) -> &str { "DELAY" }
     fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
     fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.beat_samples = tempo.beat_samples(); }
+    fn settle(&mut self) {
+        self.length.set_immediate(self.target_length());
+        self.feedback.settle();
+        self.mix.settle();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_alternate_between_sides() {
+        let mut delay = Delay::new();
+        delay.set_param(0, 0.01);
+        delay.set_param(1, 0.5);
+        delay.set_param(2, 1.0);
+        delay.settle();
+        let delay_samples = (0.01 * SAMPLE_RATE as f32) as usize;
+
+        let len = delay_samples * 2 + 1;
+        let mut left = vec![0.0; len];
+        let mut right = vec![0.0; len];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        delay.process(&mut left, &mut right);
+
+        // First repeat on the left only, the second on the right only
+        assert!(left[delay_samples] > 0.9 && right[delay_samples] == 0.0);
+        assert!(right[delay_samples * 2] > 0.4 && left[delay_samples * 2] == 0.0);
+    }
+
+    #[test]
+    fn synced_time_follows_the_tempo() {
+        let mut delay = Delay::new();
+        delay.set_param(2, 1.0);
+        delay.set_param(3, 9.0); // 1/4
+        delay.set_transport(&TempoInfo::new(150.0));
+        delay.settle();
+        let quarter = (60.0 / 150.0 * SAMPLE_RATE as f64) as usize;
+
+        let mut left = vec![0.0; quarter + 1];
+        let mut right = vec![0.0; quarter + 1];
+        left[0] = 1.0;
+        delay.process(&mut left, &mut right);
+        assert!(left[quarter] > 0.4);
+        assert!(left[..quarter].iter().all(|s| *s == 0.0));
+    }
 }
@@ -1,44 +1,66 @@
-use crate::effects::Effect;
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::{self, Effect};
 
 pub struct Distortion {
-    drive: f32,
-    mix: f32,
+    drive: SmoothedParam,
+    mix: SmoothedParam,
     bypassed: bool,
 }
 
 impl Distortion {
     pub fn new() -> Self {
         Self {
-            drive: 2.0,
-            mix: 0.5,
+            drive: SmoothedParam::new(2.0),
+            mix: SmoothedParam::new(0.5),
             bypassed: false,
         }
     }
 }
 
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 2] = [(1.0, 10.0), (0.0, 1.0)];
+
 impl Effect for Distortion {
-    fn process(&mut self, input: &mut [f32]) {
-        for sample in input.iter_mut() {
-            let dry = *sample;
-            // Tape saturation: normalized tanh waveshaping
-            let driven = (*sample * self.drive).tanh() / self.drive.tanh();
-            *sample = dry * (1.0 - self.mix) + driven * self.mix;
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let drive = self.drive.tick();
+            let mix = self.mix.tick();
+            for sample in [l, r] {
+                // Tape saturation: normalized tanh waveshaping
+                let driven = (*sample * drive).tanh() / drive.tanh();
+                *sample = *sample * (1.0 - mix) + driven * mix;
+            }
         }
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
         match index {
-            0 => self.drive = value.clamp(1.0, 10.0),
-            1 => self.mix = value.clamp(0.0, 1.0),
+            0 => self.drive.set(value),
+            1 => self.mix.set(value),
             _ => {}
         }
     }
 
-    fn param_count(&self) -> usize { 2 }
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.drive.target(),
+            1 => self.mix.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
     fn param_name(&self, index: usize) -> &str {
         match index { 0 => "DRIVE", 1 => "MIX", _ => "" }
     }
     fn name(&self) -> &str { "DIST" }
     fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
     fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) {
+        self.drive.settle();
+        self.mix.settle();
+    }
 }
@@ -0,0 +1,311 @@
+use crate::constants::SAMPLE_RATE;
+use crate::effects::{self, Effect};
+
+/// Samples between coefficient updates while a band glides to new settings
+const SMOOTH_BLOCK: usize = 32;
+/// Fraction of the remaining distance covered per update (~20 ms glide)
+const SMOOTH_STEP: f32 = 0.036;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BandKind {
+    HighPass,
+    LowShelf,
+    Peak,
+    HighShelf,
+}
+
+/// Band settings: frequency in Hz, gain in dB, Q
+#[derive(Clone, Copy, PartialEq)]
+struct BandSettings {
+    freq: f32,
+    gain: f32,
+    q: f32,
+}
+
+/// One biquad band (RBJ cookbook), gliding from its current to its target settings
+struct Band {
+    kind: BandKind,
+    target: BandSettings,
+    current: BandSettings,
+    // Normalized coefficients: b0, b1, b2, a1, a2
+    coefs: [f32; 5],
+    // Transposed direct form II state per channel
+    state: [[f32; 2]; 2],
+}
+
+impl Band {
+    fn new(kind: BandKind, freq: f32, gain: f32, q: f32) -> Self {
+        let settings = BandSettings { freq, gain, q };
+        let mut band = Self {
+            kind,
+            target: settings,
+            current: settings,
+            coefs: [1.0, 0.0, 0.0, 0.0, 0.0],
+            state: [[0.0; 2]; 2],
+        };
+        band.update_coefs();
+        band
+    }
+
+    fn update_coefs(&mut self) {
+        let BandSettings { freq, gain, q } = self.current;
+        let w0 = std::f32::consts::TAU * freq / SAMPLE_RATE as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a = 10f32.powf(gain / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let [b0, b1, b2, a0, a1, a2] = match self.kind {
+            BandKind::HighPass => [
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ],
+            BandKind::LowShelf => [
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ],
+            BandKind::Peak => [
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ],
+            BandKind::HighShelf => [
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ],
+        };
+        self.coefs = [b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0];
+    }
+
+    /// Move the current settings a step toward the target; frequency glides
+    /// in the log domain so sweeps sound even
+    fn glide(&mut self) {
+        if self.current == self.target {
+            return;
+        }
+        let (cur, tgt) = (self.current, self.target);
+        let mut next = BandSettings {
+            freq: cur.freq * (tgt.freq / cur.freq).powf(SMOOTH_STEP),
+            gain: cur.gain + (tgt.gain - cur.gain) * SMOOTH_STEP,
+            q: cur.q + (tgt.q - cur.q) * SMOOTH_STEP,
+        };
+        if (next.freq / tgt.freq - 1.0).abs() < 0.001
+            && (next.gain - tgt.gain).abs() < 0.01
+            && (next.q - tgt.q).abs() < 0.001
+        {
+            next = tgt;
+        }
+        self.current = next;
+        self.update_coefs();
+    }
+
+    /// Jump straight to the target settings
+    fn settle(&mut self) {
+        self.current = self.target;
+        self.update_coefs();
+    }
+
+    fn tick(&mut self, ch: usize, x: f32) -> f32 {
+        let [b0, b1, b2, a1, a2] = self.coefs;
+        let s = &mut self.state[ch];
+        let y = b0 * x + s[0];
+        s[0] = b1 * x - a1 * y + s[1];
+        s[1] = b2 * x - a2 * y;
+        y
+    }
+}
+
+/// Run a set of bands over a stereo block, gliding their settings as it goes
+fn process_bands(bands: &mut [Band], left: &mut [f32], right: &mut [f32]) {
+    for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+        if i % SMOOTH_BLOCK == 0 {
+            bands.iter_mut().for_each(Band::glide);
+        }
+        for band in bands.iter_mut() {
+            *l = band.tick(0, *l);
+            *r = band.tick(1, *r);
+        }
+    }
+}
+
+/// Parametric EQ: high-pass, low shelf, two peaking bands and a high shelf
+pub struct ParametricEq {
+    bands: [Band; 5],
+    bypassed: bool,
+}
+
+impl ParametricEq {
+    pub fn new() -> Self {
+        Self {
+            bands: [
+                Band::new(BandKind::HighPass, 20.0, 0.0, 0.707),
+                Band::new(BandKind::LowShelf, 100.0, 0.0, 0.707),
+                Band::new(BandKind::Peak, 500.0, 0.0, 1.0),
+                Band::new(BandKind::Peak, 3000.0, 0.0, 1.0),
+                Band::new(BandKind::HighShelf, 8000.0, 0.0, 0.707),
+            ],
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 11] = [
+    (20.0, 500.0),
+    (30.0, 500.0),
+    (-15.0, 15.0),
+    (100.0, 2000.0),
+    (-15.0, 15.0),
+    (0.3, 8.0),
+    (1000.0, 10000.0),
+    (-15.0, 15.0),
+    (0.3, 8.0),
+    (2000.0, 16000.0),
+    (-15.0, 15.0),
+];
+
+impl Effect for ParametricEq {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        process_bands(&mut self.bands, left, right);
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.bands[0].target.freq = value,
+            1 => self.bands[1].target.freq = value,
+            2 => self.bands[1].target.gain = value,
+            3 => self.bands[2].target.freq = value,
+            4 => self.bands[2].target.gain = value,
+            5 => self.bands[2].target.q = value,
+            6 => self.bands[3].target.freq = value,
+            7 => self.bands[3].target.gain = value,
+            8 => self.bands[3].target.q = value,
+            9 => self.bands[4].target.freq = value,
+            10 => self.bands[4].target.gain = value,
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        let b = &self.bands;
+        match index {
+            0 => b[0].target.freq,
+            1 => b[1].target.freq,
+            2 => b[1].target.gain,
+            3 => b[2].target.freq,
+            4 => b[2].target.gain,
+            5 => b[2].target.q,
+            6 => b[3].target.freq,
+            7 => b[3].target.gain,
+            8 => b[3].target.q,
+            9 => b[4].target.freq,
+            10 => b[4].target.gain,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index {
+            0 => "HPF",
+            1 => "LO F",
+            2 => "LO G",
+            3 => "MID1 F",
+            4 => "MID1 G",
+            5 => "MID1 Q",
+            6 => "MID2 F",
+            7 => "MID2 G",
+            8 => "MID2 Q",
+            9 => "HI F",
+            10 => "HI G",
+            _ => "",
+        }
+    }
+    fn name(&self) -> &str { "EQ" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) { self.bands.iter_mut().for_each(Band::settle); }
+}
+
+/// Bands of the fixed channel EQ on each mixer strip
+pub const CHANNEL_EQ_BANDS: usize = 3;
+pub const CHANNEL_EQ_NAMES: [&str; CHANNEL_EQ_BANDS] = ["LO", "MID", "HI"];
+/// Cut/boost range of the channel EQ in dB
+pub const CHANNEL_EQ_RANGE: f32 = 12.0;
+
+/// Portastudio-style channel EQ: low shelf at 100 Hz, mid peak at 1 kHz and
+/// high shelf at 10 kHz, with gain controls only
+pub struct ChannelEq {
+    bands: [Band; CHANNEL_EQ_BANDS],
+}
+
+impl ChannelEq {
+    pub fn new() -> Self {
+        Self {
+            bands: [
+                Band::new(BandKind::LowShelf, 100.0, 0.0, 0.707),
+                Band::new(BandKind::Peak, 1000.0, 0.0, 0.7),
+                Band::new(BandKind::HighShelf, 10000.0, 0.0, 0.707),
+            ],
+        }
+    }
+
+    pub fn set_gain(&mut self, band: usize, gain: f32) {
+        if let Some(band) = self.bands.get_mut(band) {
+            band.target.gain = gain.clamp(-CHANNEL_EQ_RANGE, CHANNEL_EQ_RANGE);
+        }
+    }
+
+    /// Flat bands are skipped so an untouched strip costs nothing
+    pub fn is_flat(&self) -> bool {
+        self.bands.iter().all(|b| b.current.gain == 0.0 && b.target.gain == 0.0)
+    }
+
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if !self.is_flat() {
+            process_bands(&mut self.bands, left, right);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine_peak(eq: &mut ChannelEq, freq: f32) -> f32 {
+        let len = SAMPLE_RATE as usize / 2;
+        let mut left: Vec<f32> = (0..len)
+            .map(|i| (std::f32::consts::TAU * freq * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        let mut right = left.clone();
+        eq.process(&mut left, &mut right);
+        left[len / 2..].iter().fold(0.0f32, |p, s| p.max(s.abs()))
+    }
+
+    #[test]
+    fn channel_eq_boosts_only_its_band() {
+        let mut eq = ChannelEq::new();
+        eq.set_gain(0, 12.0);
+        // +12 dB is about 4x in the lows, while the highs stay put
+        assert!((sine_peak(&mut eq, 30.0) - 3.98).abs() < 0.3);
+        assert!((sine_peak(&mut eq, 12000.0) - 1.0).abs() < 0.05);
+    }
+}
@@ -1,15 +1,18 @@
-use crate::effects::Effect;
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::{self, Effect};
 
 pub struct Filter {
-    cutoff: f32,
-    resonance: f32,
+    cutoff: SmoothedParam,
+    resonance: SmoothedParam,
     mode: FilterMode,
-    lp: f32,
-    bp: f32,
+    // Integrator state per channel
+    lp: [f32; 2],
+    bp: [f32; 2],
     bypassed: bool,
 }
 
 #[derive(Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
 pub enum FilterMode {
     LowPass,
     HighPass,
@@ -19,56 +22,77 @@ pub enum FilterMode {
 impl Filter {
     pub fn new() -> Self {
         Self {
-            cutoff: 0.5,
-            resonance: 0.3,
+            cutoff: SmoothedParam::new(0.5),
+            resonance: SmoothedParam::new(0.3),
             mode: FilterMode::LowPass,
-            lp: 0.0,
-            bp: 0.0,
+            lp: [0.0; 2],
+            bp: [0.0; 2],
             bypassed: false,
         }
     }
 }
 
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 3] = [(0.01, 1.0), (0.0, 0.95), (0.0, 2.0)];
+
 impl Effect for Filter {
-    fn process(&mut self, input: &mut [f32]) {
-        let f = (self.cutoff * self.cutoff).clamp(0.001, 0.99);
-        let q = 1.0 - self.resonance.clamp(0.0, 0.95);
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let cutoff = self.cutoff.tick();
+            let f = (cutoff * cutoff).clamp(0.001, 0.99);
+            let q = 1.0 - self.resonance.tick().clamp(0.0, 0.95);
 
-        for sample in input.iter_mut() {
-            self.lp += f * self.bp;
-            let hp = *sample - self.lp - q * self.bp;
-            self.bp += f * hp;
+            for (ch, sample) in [l, r].into_iter().enumerate() {
+                let (lp, bp) = (&mut self.lp[ch], &mut self.bp[ch]);
+                *lp += f * *bp;
+                let hp = *sample - *lp - q * *bp;
+                *bp += f * hp;
 
-            *sample = match self.mode {
-                FilterMode::LowPass => self.lp,
-                FilterMode::HighPass => hp,
-                FilterMode::BandPass => self.bp,
-            };
+                *sample = match self.mode {
+                    FilterMode::LowPass => *lp,
+                    FilterMode::HighPass => hp,
+                    FilterMode::BandPass => *bp,
+                };
+            }
         }
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
         match index {
-            0 => self.cutoff = value.clamp(0.01, 1.0),
-            1 => self.resonance = value.clamp(0.0, 0.95),
+            0 => self.cutoff.set(value),
+            1 => self.resonance.set(value),
             2 => {
-                self.mode = if value < 0.33 {
-                    FilterMode::LowPass
-                } else if value < 0.66 {
-                    FilterMode::HighPass
-                } else {
-                    FilterMode::BandPass
+                self.mode = match value.round() as i32 {
+                    i32::MIN..=0 => FilterMode::LowPass,
+                    1 => FilterMode::HighPass,
+                    _ => FilterMode::BandPass,
                 };
             }
             _ => {}
         }
     }
 
-    fn param_count(&self) -> usize { 3 }
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.cutoff.target(),
+            1 => self.resonance.target(),
+            2 => self.mode as usize as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
     fn param_name(&self, index: usize) -> &str {
         match index { 0 => "CUTOFF", 1 => "RESO", 2 => "MODE", _ => "" }
     }
     fn name(&self) -> &str { "FILTER" }
     fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
     fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) {
+        self.cutoff.settle();
+        self.resonance.settle();
+    }
 }
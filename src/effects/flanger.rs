@@ -0,0 +1,108 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::SAMPLE_RATE;
+use crate::effects::tempo::{self, SyncedLfo, TempoInfo, BAR_CYCLES};
+use crate::effects::{self, read_frac, Effect};
+
+/// Shortest sweep delay in ms
+const MIN_DELAY_MS: f32 = 0.5;
+
+/// Modulated short delay with feedback; the right side sweeps in antiphase
+pub struct Flanger {
+    buffer_l: Vec<f32>,
+    buffer_r: Vec<f32>,
+    write_pos: usize,
+    lfo: SyncedLfo,
+    depth: SmoothedParam, // ms
+    feedback: SmoothedParam,
+    mix: SmoothedParam,
+    bypassed: bool,
+}
+
+impl Flanger {
+    pub fn new() -> Self {
+        // Room for the deepest sweep plus interpolation
+        let len = (0.02 * SAMPLE_RATE as f32) as usize;
+        Self {
+            buffer_l: vec![0.0; len],
+            buffer_r: vec![0.0; len],
+            write_pos: 0,
+            lfo: SyncedLfo::new(0.2),
+            depth: SmoothedParam::new(3.0),
+            feedback: SmoothedParam::new(0.6),
+            mix: SmoothedParam::new(0.5),
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 5] = [
+    (0.05, 5.0),
+    (0.1, 10.0),
+    (-0.95, 0.95),
+    (0.0, 1.0),
+    (0.0, (BAR_CYCLES.len() - 1) as f32),
+];
+
+impl Effect for Flanger {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let ms = SAMPLE_RATE as f32 * 0.001;
+        let len = self.buffer_l.len();
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mix = self.mix.tick();
+            let depth = self.depth.tick();
+            let feedback = self.feedback.tick();
+            let lfo = (self.lfo.tick() * std::f64::consts::TAU).sin() as f32;
+            let delay_at = |lfo: f32| (MIN_DELAY_MS + depth * (0.5 + 0.5 * lfo)) * ms;
+            let wet_l = read_frac(&self.buffer_l, self.write_pos, delay_at(lfo));
+            let wet_r = read_frac(&self.buffer_r, self.write_pos, delay_at(-lfo));
+
+            self.buffer_l[self.write_pos] = *l + wet_l * feedback;
+            self.buffer_r[self.write_pos] = *r + wet_r * feedback;
+            self.write_pos = (self.write_pos + 1) % len;
+
+            *l = *l * (1.0 - mix) + wet_l * mix;
+            *r = *r * (1.0 - mix) + wet_r * mix;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.lfo.set_rate(value),
+            1 => self.depth.set(value),
+            2 => self.feedback.set(value),
+            3 => self.mix.set(value),
+            4 => self.lfo.sync = tempo::sync_index(value, BAR_CYCLES.len()),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.lfo.rate(),
+            1 => self.depth.target(),
+            2 => self.feedback.target(),
+            3 => self.mix.target(),
+            4 => self.lfo.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "RATE", 1 => "DEPTH", 2 => "FDBK", 3 => "MIX", 4 => "SYNC", _ => "" }
+    }
+    fn name(&self) -> &str { "FLANGER" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.lfo.set_transport(tempo); }
+    fn settle(&mut self) {
+        self.depth.settle();
+        self.feedback.settle();
+        self.mix.settle();
+    }
+}
@@ -0,0 +1,167 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::{MAX_BLOCK_FRAMES, SAMPLE_RATE, TRACK_COUNT};
+use crate::effects::{self, copy_key, detector_level, time_coef, to_db, Effect, Sidechain};
+
+/// Downward expander; at high ratios it works as a noise gate
+pub struct Gate {
+    threshold: SmoothedParam, // dB
+    ratio: SmoothedParam,
+    attack: f32,  // ms
+    hold: f32,    // ms
+    release: f32, // ms
+    range: SmoothedParam, // dB, deepest attenuation
+    sidechain: Option<Sidechain>,
+    key: Vec<f32>,
+    // Current gain change in dB (zero or negative)
+    gain: f32,
+    hold_left: usize,
+    reduction: f32,
+    bypassed: bool,
+}
+
+impl Gate {
+    pub fn new() -> Self {
+        Self {
+            threshold: SmoothedParam::new(-50.0),
+            ratio: SmoothedParam::new(10.0),
+            attack: 1.0,
+            hold: 50.0,
+            release: 100.0,
+            range: SmoothedParam::new(60.0),
+            sidechain: None,
+            key: Vec::with_capacity(MAX_BLOCK_FRAMES),
+            gain: 0.0,
+            hold_left: 0,
+            reduction: 0.0,
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 7] = [
+    (-80.0, 0.0),
+    (1.0, 20.0),
+    (0.1, 50.0),
+    (0.0, 500.0),
+    (10.0, 1000.0),
+    (0.0, 80.0),
+    (0.0, (TRACK_COUNT + 1) as f32),
+];
+
+impl Effect for Gate {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let attack = time_coef(self.attack);
+        let release = time_coef(self.release);
+        let hold = (self.hold * 0.001 * SAMPLE_RATE as f32) as usize;
+        let key = self.sidechain.map(|_| self.key.as_slice());
+        self.reduction = 0.0;
+
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let level = to_db(detector_level(key, i, *l, *r));
+            let under = (level - self.threshold.tick()).min(0.0);
+            let target = (under * (self.ratio.tick() - 1.0)).max(-self.range.tick());
+
+            // Open quickly, then stay open for the hold time before closing
+            if under == 0.0 {
+                self.hold_left = hold;
+            }
+            if target > self.gain {
+                self.gain = target + (self.gain - target) * attack;
+            } else if self.hold_left > 0 {
+                self.hold_left -= 1;
+            } else {
+                self.gain = target + (self.gain - target) * release;
+            }
+
+            let gain = 10f32.powf(self.gain / 20.0);
+            *l *= gain;
+            *r *= gain;
+            self.reduction = self.reduction.max(-self.gain);
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.threshold.set(value),
+            1 => self.ratio.set(value),
+            2 => self.attack = value,
+            3 => self.hold = value,
+            4 => self.release = value,
+            5 => self.range.set(value),
+            6 => self.sidechain = Sidechain::from_param(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.threshold.target(),
+            1 => self.ratio.target(),
+            2 => self.attack,
+            3 => self.hold,
+            4 => self.release,
+            5 => self.range.target(),
+            6 => Sidechain::to_param(self.sidechain),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index {
+            0 => "THRESH",
+            1 => "RATIO",
+            2 => "ATTACK",
+            3 => "HOLD",
+            4 => "RELEASE",
+            5 => "RANGE",
+            6 => "KEY",
+            _ => "",
+        }
+    }
+    fn name(&self) -> &str { "GATE" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn gain_reduction(&self) -> f32 { self.reduction }
+    fn sidechain(&self) -> Option<Sidechain> { self.sidechain }
+    fn set_key(&mut self, key: &[f32]) { copy_key(&mut self.key, key); }
+    fn settle(&mut self) {
+        for param in [&mut self.threshold, &mut self.ratio, &mut self.range] {
+            param.settle();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_open_then_attenuates_below_the_threshold() {
+        let mut gate = Gate::new();
+        let hold = (50.0 * 0.001 * SAMPLE_RATE as f32) as usize;
+
+        let mut left = vec![0.5; 512];
+        let mut right = left.clone();
+        gate.process(&mut left, &mut right);
+        assert_eq!(left[511], 0.5);
+
+        // -60 dB input, 10 dB under the threshold: untouched for the hold time
+        let mut left = vec![0.001; hold - 10];
+        let mut right = left.clone();
+        gate.process(&mut left, &mut right);
+        assert!(left.iter().all(|s| *s == 0.001));
+        assert_eq!(gate.gain_reduction(), 0.0);
+
+        // Then the gate closes down to its 60 dB range
+        let mut left = vec![0.001; SAMPLE_RATE as usize];
+        let mut right = left.clone();
+        gate.process(&mut left, &mut right);
+        assert!(left[left.len() - 1] < 0.001 * 10f32.powf(-59.0 / 20.0));
+        assert!(gate.gain_reduction() > 59.0 && gate.gain_reduction() <= 60.0);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::SAMPLE_RATE;
+use crate::effects::{self, time_coef, Effect};
+
+/// How far ahead the limiter looks for peaks
+const LOOKAHEAD_MS: f32 = 1.5;
+
+/// Lookahead true-peak limiter for the end of the master bus.
+///
+/// The signal is delayed by the lookahead so gain can ramp down before a peak
+/// arrives; peaks between samples are estimated by interpolating each segment.
+pub struct Limiter {
+    ceiling: SmoothedParam, // dBFS
+    release: f32, // ms
+    lookahead: usize,
+    // Audio delay line, lookahead + 1 samples long
+    delay_l: Vec<f32>,
+    delay_r: Vec<f32>,
+    delay_pos: usize,
+    // Last three input samples per channel, oldest first
+    history: [[f32; 3]; 2],
+    // Required gains over the lookahead window, then their held minimums
+    targets: Vec<f32>,
+    held: Vec<f32>,
+    window_pos: usize,
+    gain: f32,
+    reduction: f32,
+    bypassed: bool,
+}
+
+impl Limiter {
+    pub fn new() -> Self {
+        let lookahead = (LOOKAHEAD_MS * 0.001 * SAMPLE_RATE as f32) as usize;
+        Self {
+            ceiling: SmoothedParam::new(-1.0),
+            release: 100.0,
+            lookahead,
+            delay_l: vec![0.0; lookahead + 1],
+            delay_r: vec![0.0; lookahead + 1],
+            delay_pos: 0,
+            history: [[0.0; 3]; 2],
+            targets: vec![1.0; lookahead],
+            held: vec![1.0; lookahead],
+            window_pos: 0,
+            gain: 1.0,
+            reduction: 0.0,
+            bypassed: false,
+        }
+    }
+
+    /// Largest magnitude on the segment ending at `x2`, including inter-sample
+    /// peaks estimated with a Catmull-Rom curve through four samples
+    fn true_peak(x0: f32, x1: f32, x2: f32, x3: f32) -> f32 {
+        let mut peak = x1.abs().max(x2.abs());
+        for t in [0.25f32, 0.5, 0.75] {
+            let t2 = t * t;
+            let t3 = t2 * t;
+            let y = 0.5
+                * (2.0 * x1
+                    + (x2 - x0) * t
+                    + (2.0 * x0 - 5.0 * x1 + 4.0 * x2 - x3) * t2
+                    + (3.0 * x1 - x0 - 3.0 * x2 + x3) * t3);
+            peak = peak.max(y.abs());
+        }
+        peak
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 2] = [(-12.0, 0.0), (10.0, 1000.0)];
+
+impl Effect for Limiter {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let mut ceiling = 10f32.powf(self.ceiling.value() / 20.0);
+        let release = time_coef(self.release);
+        self.reduction = 0.0;
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            // Only convert the ceiling while it is gliding
+            if self.ceiling.is_smoothing() {
+                self.ceiling.tick();
+                ceiling = 10f32.powf(self.ceiling.value() / 20.0);
+            }
+            let mut peak = 0.0f32;
+            for (ch, x) in [*l, *r].into_iter().enumerate() {
+                let [x0, x1, x2] = self.history[ch];
+                peak = peak.max(Self::true_peak(x0, x1, x2, x));
+                self.history[ch] = [x1, x2, x];
+            }
+
+            // Hold the lowest required gain across the window, then smooth it
+            // with a moving average so the ramp is complete when the peak is output
+            let target = if peak > ceiling { ceiling / peak } else { 1.0 };
+            self.targets[self.window_pos] = target;
+            self.held[self.window_pos] = self.targets.iter().copied().fold(1.0, f32::min);
+            self.window_pos = (self.window_pos + 1) % self.lookahead;
+            let smoothed = self.held.iter().sum::<f32>() / self.lookahead as f32;
+
+            self.gain = if smoothed < self.gain {
+                smoothed
+            } else {
+                smoothed + (self.gain - smoothed) * release
+            };
+
+            let delayed_l = self.delay_l[self.delay_pos];
+            let delayed_r = self.delay_r[self.delay_pos];
+            self.delay_l[self.delay_pos] = *l;
+            self.delay_r[self.delay_pos] = *r;
+            self.delay_pos = (self.delay_pos + 1) % self.delay_l.len();
+
+            *l = delayed_l * self.gain;
+            *r = delayed_r * self.gain;
+            self.reduction = self.reduction.max(-20.0 * self.gain.log10());
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.ceiling.set(value),
+            1 => self.release = value,
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.ceiling.target(),
+            1 => self.release,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "CEIL", 1 => "RELEASE", _ => "" }
+    }
+    fn name(&self) -> &str { "LIMIT" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn gain_reduction(&self) -> f32 { self.reduction }
+    fn settle(&mut self) { self.ceiling.settle(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_stays_under_the_ceiling() {
+        let mut limiter = Limiter::new();
+        let ceiling = 10f32.powf(-1.0 / 20.0);
+
+        let mut left: Vec<f32> = (0..4096)
+            .map(|i| (i as f32 * 0.37).sin() * 3.0)
+            .collect();
+        let mut right = left.clone();
+        limiter.process(&mut left, &mut right);
+
+        assert!(left.iter().chain(&right).all(|s| s.abs() <= ceiling + 1e-4));
+        assert!(limiter.gain_reduction() > 9.0);
+    }
+}
@@ -3,16 +3,198 @@ pub mod delay;
 pub mod filter;
 pub mod distortion;
 pub mod chorus;
+pub mod limiter;
+pub mod compressor;
+pub mod gate;
+pub mod eq;
+pub mod tempo;
+pub mod bitcrusher;
+pub mod phaser;
+pub mod flanger;
+pub mod tremolo;
+pub mod autopan;
+pub mod ringmod;
+pub mod snapshot;
 
-/// Trait for an audio effect
+use crate::constants::{MAX_BLOCK_FRAMES, TRACK_COUNT};
+use tempo::TempoInfo;
+
+/// Trait for an audio effect. Processes a block of stereo frames in place;
+/// `left` and `right` always have the same length.
 pub trait Effect: Send {
-    fn process(&mut self, input: &mut [f32]);
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]);
     fn set_param(&mut self, index: usize, value: f32);
+    /// Current value of a parameter, in the units `set_param` takes
+    fn get_param(&self, index: usize) -> f32;
+    /// Lowest and highest value `set_param` accepts for a parameter
+    fn param_range(&self, index: usize) -> (f32, f32);
     fn param_count(&self) -> usize;
     fn param_name(&self, index: usize) -> &str;
     fn name(&self) -> &str;
     fn set_bypass(&mut self, bypass: bool);
     fn is_bypassed(&self) -> bool;
+    /// Gain reduction in dB over the last processed block, for dynamics processors
+    fn gain_reduction(&self) -> f32 {
+        0.0
+    }
+    /// External signal the effect's level detector listens to, if any
+    fn sidechain(&self) -> Option<Sidechain> {
+        None
+    }
+    /// Key signal for the next `process` block, fed when `sidechain` is set
+    fn set_key(&mut self, _key: &[f32]) {}
+    /// Tempo and transport position for the next `process` block
+    fn set_transport(&mut self, _tempo: &TempoInfo) {}
+    /// Jump smoothed parameters straight to their targets, for settings made
+    /// before the effect is heard rather than while it plays
+    fn settle(&mut self) {}
+}
+
+impl std::fmt::Debug for dyn Effect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Effect").field("name", &self.name()).finish()
+    }
+}
+
+pub type EffectConstructor = fn() -> Box<dyn Effect>;
+
+/// Effect registry: name and constructor of each effect offered in the
+/// track insert slots, in selection order
+pub const EFFECT_COUNT: usize = 14;
+pub const EFFECTS: [(&str, EffectConstructor); EFFECT_COUNT] = [
+    ("REVERB", || Box::new(reverb::Reverb::new())),
+    ("DELAY", || Box::new(delay::Delay::new())),
+    ("FILTER", || Box::new(filter::Filter::new())),
+    ("DIST", || Box::new(distortion::Distortion::new())),
+    ("CHORUS", || Box::new(chorus::Chorus::new())),
+    ("COMP", || Box::new(compressor::Compressor::new())),
+    ("GATE", || Box::new(gate::Gate::new())),
+    ("EQ", || Box::new(eq::ParametricEq::new())),
+    ("CRUSH", || Box::new(bitcrusher::Bitcrusher::new())),
+    ("PHASER", || Box::new(phaser::Phaser::new())),
+    ("FLANGER", || Box::new(flanger::Flanger::new())),
+    ("TREMOLO", || Box::new(tremolo::Tremolo::new())),
+    ("AUTOPAN", || Box::new(autopan::AutoPan::new())),
+    ("RINGMOD", || Box::new(ringmod::RingMod::new())),
+];
+
+/// Build the registry effect at `index`. Called on the UI thread; the audio
+/// thread only receives finished effects.
+pub fn create_effect(index: usize) -> Option<Box<dyn Effect>> {
+    EFFECTS.get(index).map(|(_, create)| create())
+}
+
+/// Registry index of the effect called `name`
+pub fn effect_index(name: &str) -> Option<usize> {
+    EFFECTS.iter().position(|(n, _)| n.eq_ignore_ascii_case(name))
+}
+
+/// All parameter values of an effect, in `param_name` order
+pub fn param_values(effect: &dyn Effect) -> Vec<f32> {
+    (0..effect.param_count()).map(|i| effect.get_param(i)).collect()
+}
+
+/// Parameter names of the effect at `index`
+pub fn param_names(index: usize) -> Vec<String> {
+    create_effect(index).map_or(vec![], |effect| {
+        (0..effect.param_count())
+            .map(|i| effect.param_name(i).to_string())
+            .collect()
+    })
+}
+
+/// Parameter ranges of the effect at `index`
+pub fn param_ranges(index: usize) -> Vec<(f32, f32)> {
+    create_effect(index).map_or(vec![], |effect| (0..effect.param_count()).map(|i| effect.param_range(i)).collect())
+}
+
+/// Default parameter values of the effect at `index`
+pub fn default_params(index: usize) -> Vec<f32> {
+    create_effect(index).map_or(vec![], |effect| param_values(effect.as_ref()))
+}
+
+/// Clamp a parameter to its entry in an effect's `PARAM_RANGES`
+pub(crate) fn clamp_param(ranges: &[(f32, f32)], index: usize, value: f32) -> f32 {
+    ranges.get(index).map_or(value, |(min, max)| value.clamp(*min, *max))
+}
+
+/// Entry `index` of an effect's `PARAM_RANGES`, or 0-1 past the end
+pub(crate) fn param_range(ranges: &[(f32, f32)], index: usize) -> (f32, f32) {
+    ranges.get(index).copied().unwrap_or((0.0, 1.0))
+}
+
+/// Editing step for a parameter: whole-number ranges of up to 16 (modes, sync
+/// divisions, keys, bits) move one at a time, everything else in 50 steps
+pub fn param_step((min, max): (f32, f32)) -> f32 {
+    let span = max - min;
+    if min.fract() == 0.0 && max.fract() == 0.0 && (2.0..=16.0).contains(&span) {
+        1.0
+    } else {
+        span / 50.0
+    }
+}
+
+/// Sidechain key source for dynamics processors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sidechain {
+    Track(usize),
+    Drums,
+}
+
+impl Sidechain {
+    /// Decode a KEY parameter: 0 = own input, 1-4 = a track, 5 = the drum kit
+    pub fn from_param(value: f32) -> Option<Sidechain> {
+        match value.round() as usize {
+            0 => None,
+            n if n <= TRACK_COUNT => Some(Sidechain::Track(n - 1)),
+            _ => Some(Sidechain::Drums),
+        }
+    }
+
+    /// Encode a key source as a KEY parameter value
+    pub fn to_param(sidechain: Option<Sidechain>) -> f32 {
+        match sidechain {
+            None => 0.0,
+            Some(Sidechain::Track(t)) => (t + 1) as f32,
+            Some(Sidechain::Drums) => (TRACK_COUNT + 1) as f32,
+        }
+    }
+}
+
+/// Detector input for frame `i`: the key signal when there is one, else the input peak
+fn detector_level(key: Option<&[f32]>, i: usize, left: f32, right: f32) -> f32 {
+    match key {
+        Some(key) => key.get(i).map_or(0.0, |k| k.abs()),
+        None => left.abs().max(right.abs()),
+    }
+}
+
+/// Read `delay` samples behind `write_pos` with linear interpolation.
+/// `delay` must be at least 1 and less than the buffer length minus 1.
+fn read_frac(buffer: &[f32], write_pos: usize, delay: f32) -> f32 {
+    let len = buffer.len();
+    let whole = delay.floor() as usize;
+    let frac = delay - whole as f32;
+    let a = buffer[(write_pos + len - whole) % len];
+    let b = buffer[(write_pos + len - whole - 1) % len];
+    a + (b - a) * frac
+}
+
+/// Copy a sidechain key into an effect's buffer, preallocated with
+/// `MAX_BLOCK_FRAMES` of capacity so the audio thread never grows it
+fn copy_key(buffer: &mut Vec<f32>, key: &[f32]) {
+    buffer.clear();
+    buffer.extend_from_slice(&key[..key.len().min(MAX_BLOCK_FRAMES)]);
+}
+
+/// Convert a level to dB, floored well below audibility
+fn to_db(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
+/// One-pole smoothing coefficient for a time constant in milliseconds
+fn time_coef(ms: f32) -> f32 {
+    (-1.0 / (ms * 0.001 * crate::constants::SAMPLE_RATE as f32)).exp()
 }
 
 /// Chain of effects applied to a track
@@ -25,10 +207,10 @@ impl EffectChain {
         Self { effects: vec![] }
     }
 
-    pub fn process(&mut self, buffer: &mut [f32]) {
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
         for effect in &mut self.effects {
             if !effect.is_bypassed() {
-                effect.process(buffer);
+                effect.process(left, right);
             }
         }
     }
@@ -37,3 +219,26 @@ impl EffectChain {
         self.effects.push(effect);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_names_match_the_effects() {
+        for (index, (name, _)) in EFFECTS.iter().enumerate() {
+            assert_eq!(create_effect(index).unwrap().name(), *name);
+            assert_eq!(effect_index(name), Some(index));
+        }
+        assert!(create_effect(EFFECT_COUNT).is_none());
+    }
+
+    #[test]
+    fn defaults_lie_within_their_ranges() {
+        for (index, (name, _)) in EFFECTS.iter().enumerate() {
+            for (value, (min, max)) in default_params(index).iter().zip(param_ranges(index)) {
+                assert!((min..=max).contains(value), "{} {}", name, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::SAMPLE_RATE;
+use crate::effects::tempo::{self, SyncedLfo, TempoInfo, BAR_CYCLES};
+use crate::effects::{self, Effect};
+
+const STAGES: usize = 6;
+const MIN_FREQ: f32 = 200.0;
+const MAX_FREQ: f32 = 4000.0;
+
+/// Six-stage allpass phaser; the right side sweeps a quarter cycle behind
+pub struct Phaser {
+    lfo: SyncedLfo,
+    depth: SmoothedParam,
+    feedback: SmoothedParam,
+    mix: SmoothedParam,
+    // First-order allpass state per channel and stage
+    stages: [[f32; STAGES]; 2],
+    last: [f32; 2],
+    bypassed: bool,
+}
+
+impl Phaser {
+    pub fn new() -> Self {
+        Self {
+            lfo: SyncedLfo::new(0.3),
+            depth: SmoothedParam::new(0.8),
+            feedback: SmoothedParam::new(0.5),
+            mix: SmoothedParam::new(0.5),
+            stages: [[0.0; STAGES]; 2],
+            last: [0.0; 2],
+            bypassed: false,
+        }
+    }
+
+    /// Allpass coefficient for a sweep position (0..1)
+    fn coefficient(position: f32) -> f32 {
+        let freq = MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(position);
+        let t = (std::f32::consts::PI * freq / SAMPLE_RATE as f32).tan();
+        (t - 1.0) / (t + 1.0)
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 5] = [
+    (0.05, 5.0),
+    (0.0, 1.0),
+    (0.0, 0.9),
+    (0.0, 1.0),
+    (0.0, (BAR_CYCLES.len() - 1) as f32),
+];
+
+impl Effect for Phaser {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let phase = self.lfo.tick();
+            let mix = self.mix.tick();
+            let depth = self.depth.tick();
+            let feedback = self.feedback.tick();
+            for (ch, sample) in [l, r].into_iter().enumerate() {
+                let lfo = (((phase + ch as f64 * 0.25) * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32;
+                let a = Self::coefficient(lfo * depth);
+
+                let mut x = *sample + self.last[ch] * feedback;
+                for state in self.stages[ch].iter_mut() {
+                    let y = a * x + *state;
+                    *state = x - a * y;
+                    x = y;
+                }
+                self.last[ch] = x;
+                *sample = *sample * (1.0 - mix) + x * mix;
+            }
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.lfo.set_rate(value),
+            1 => self.depth.set(value),
+            2 => self.feedback.set(value),
+            3 => self.mix.set(value),
+            4 => self.lfo.sync = tempo::sync_index(value, BAR_CYCLES.len()),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.lfo.rate(),
+            1 => self.depth.target(),
+            2 => self.feedback.target(),
+            3 => self.mix.target(),
+            4 => self.lfo.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "RATE", 1 => "DEPTH", 2 => "FDBK", 3 => "MIX", 4 => "SYNC", _ => "" }
+    }
+    fn name(&self) -> &str { "PHASER" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.lfo.set_transport(tempo); }
+    fn settle(&mut self) {
+        self.depth.settle();
+        self.feedback.settle();
+        self.mix.settle();
+    }
+}
@@ -1,65 +1,206 @@
-use crate::effects::Effect;
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::SAMPLE_RATE;
+use crate::effects::{self, read_frac, Effect};
 
+// Freeverb tunings, in samples at 44.1 kHz
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// Extra length of the right channel's lines, decorrelating the two sides
+const STEREO_SPREAD: usize = 23;
+const INPUT_GAIN: f32 = 0.015;
+const WET_GAIN: f32 = 3.0;
+const MAX_PREDELAY_MS: f32 = 200.0;
+
+/// Scale a 44.1 kHz tuning to the running sample rate
+fn scaled(samples: usize) -> usize {
+    (samples as f64 * SAMPLE_RATE as f64 / 44_100.0).round().max(1.0) as usize
+}
+
+/// Feedback comb with a one-pole lowpass in the loop
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    filter_state: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], pos: 0, filter_state: 0.0 }
+    }
+
+    fn tick(&mut self, input: f32, feedback: f32, damp: f32) -> f32 {
+        let out = self.buffer[self.pos];
+        self.filter_state = out * (1.0 - damp) + self.filter_state * damp;
+        self.buffer[self.pos] = input + self.filter_state * feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        out
+    }
+}
+
+/// Schroeder allpass used for diffusion
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], pos: 0 }
+    }
+
+    fn tick(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.pos];
+        self.buffer[self.pos] = input + delayed * 0.5;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Freeverb-style stereo reverb: parallel damped combs into series allpasses
+/// per side, with pre-delay and adjustable stereo width
 pub struct Reverb {
-    delay_lines: [Vec<f32>; 4],
-    write_positions: [usize; 4],
-    mix: f32,
-    decay: f32,
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<Allpass>; 2],
+    predelay_buffer: Vec<f32>,
+    predelay_pos: usize,
+    mix: SmoothedParam,
+    size: SmoothedParam,
+    damping: SmoothedParam,
+    predelay: SmoothedParam, // ms
+    width: SmoothedParam,
     bypassed: bool,
 }
 
 impl Reverb {
     pub fn new() -> Self {
-        // 4 delay lines with prime-number lengths for diffuse reverb
-        let lengths = [1557, 1617, 1491, 1422];
+        let side = |spread: usize| {
+            (
+                COMB_TUNINGS.iter().map(|&t| Comb::new(scaled(t + spread))).collect(),
+                ALLPASS_TUNINGS.iter().map(|&t| Allpass::new(scaled(t + spread))).collect(),
+            )
+        };
+        let (combs_l, allpasses_l) = side(0);
+        let (combs_r, allpasses_r) = side(STEREO_SPREAD);
+        // Room for the longest pre-delay plus interpolation
+        let max_predelay = (MAX_PREDELAY_MS * 0.001 * SAMPLE_RATE as f32) as usize + 3;
         Self {
-            delay_lines: [
-                vec![0.0; lengths[0]],
-                vec![0.0; lengths[1]],
-                vec![0.0; lengths[2]],
-                vec![0.0; lengths[3]],
-            ],
-            write_positions: [0; 4],
-            mix: 0.3,
-            decay: 0.6,
+            combs: [combs_l, combs_r],
+            allpasses: [allpasses_l, allpasses_r],
+            predelay_buffer: vec![0.0; max_predelay],
+            predelay_pos: 0,
+            mix: SmoothedParam::new(0.3),
+            size: SmoothedParam::new(0.6),
+            damping: SmoothedParam::new(0.5),
+            predelay: SmoothedParam::new(10.0),
+            width: SmoothedParam::new(1.0),
             bypassed: false,
         }
     }
 }
 
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 5] = [
+    (0.0, 1.0),
+    (0.0, 1.0),
+    (0.0, 1.0),
+    (0.0, MAX_PREDELAY_MS),
+    (0.0, 1.0),
+];
+
 impl Effect for Reverb {
-    fn process(&mut self, input: &mut [f32]) {
-        for sample in input.iter_mut() {
-            let dry = *sample;
-            let mut wet = 0.0f32;
-
-            for i in 0..4 {
-                let len = self.delay_lines[i].len();
-                let read_pos = (self.write_positions[i] + 1) % len;
-                let delayed = self.delay_lines[i][read_pos];
-                wet += delayed * 0.25;
-
-                self.delay_lines[i][self.write_positions[i]] = dry + delayed * self.decay;
-                self.write_positions[i] = (self.write_positions[i] + 1) % len;
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let buf_len = self.predelay_buffer.len();
+        let ms = 0.001 * SAMPLE_RATE as f32;
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let feedback = self.size.tick() * 0.28 + 0.7;
+            let damp = self.damping.tick() * 0.4;
+            let width = self.width.tick();
+            let same = width / 2.0 + 0.5;
+            let cross = (1.0 - width) / 2.0;
+
+            // The input is written first, so it is read one sample further back
+            // than the pre-delay and a pre-delay of zero passes it straight on
+            self.predelay_buffer[self.predelay_pos] = (*l + *r) * INPUT_GAIN;
+            self.predelay_pos = (self.predelay_pos + 1) % buf_len;
+            let input = read_frac(&self.predelay_buffer, self.predelay_pos, self.predelay.tick() * ms + 1.0);
+
+            let mut out = [0.0f32; 2];
+            for (ch, out) in out.iter_mut().enumerate() {
+                let mut acc: f32 = self.combs[ch].iter_mut().map(|c| c.tick(input, feedback, damp)).sum();
+                for allpass in &mut self.allpasses[ch] {
+                    acc = allpass.tick(acc);
+                }
+                *out = acc;
             }
 
-            *sample = dry * (1.0 - self.mix) + wet * self.mix;
+            let mix = self.mix.tick();
+            let (wet_same, wet_cross) = (mix * WET_GAIN * same, mix * WET_GAIN * cross);
+            *l = *l * (1.0 - mix) + out[0] * wet_same + out[1] * wet_cross;
+            *r = *r * (1.0 - mix) + out[1] * wet_same + out[0] * wet_cross;
         }
     }
 
     fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
         match index {
-            0 => self.mix = value.clamp(0.0, 1.0),
-            1 => self.decay = value.clamp(0.1, 0.95),
+            0 => self.mix.set(value),
+            1 => self.size.set(value),
+            2 => self.damping.set(value),
+            3 => self.predelay.set(value),
+            4 => self.width.set(value),
             _ => {}
         }
     }
 
-    fn param_count(&self) -> usize { 2 }
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.mix.target(),
+            1 => self.size.target(),
+            2 => self.damping.target(),
+            3 => self.predelay.target(),
+            4 => self.width.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
     fn param_name(&self, index: usize) -> &str {
-        match index { 0 => "MIX", 1 => "DECAY", _ => "" }
+        match index { 0 => "MIX", 1 => "SIZE", 2 => "DAMP", 3 => "PREDLY", 4 => "WIDTH", _ => "" }
     }
     fn name(&self) -> &str { "REVERB" }
     fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
     fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) {
+        for param in [&mut self.mix, &mut self.size, &mut self.damping, &mut self.predelay, &mut self.width] {
+            param.settle();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_starts_after_predelay_and_differs_per_side() {
+        let mut reverb = Reverb::new();
+        reverb.set_param(0, 1.0);
+        reverb.set_param(3, 50.0);
+        reverb.settle();
+
+        let len = SAMPLE_RATE as usize / 2;
+        let mut left = vec![0.0; len];
+        let mut right = vec![0.0; len];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right);
+
+        let predelay = (0.05 * SAMPLE_RATE as f32) as usize;
+        assert!(left[..predelay].iter().all(|s| *s == 0.0));
+        assert!(left[predelay..].iter().any(|s| s.abs() > 1e-3));
+        assert!(left.iter().zip(&right).any(|(l, r)| (l - r).abs() > 1e-3));
+    }
 }
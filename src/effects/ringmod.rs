@@ -0,0 +1,70 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::constants::SAMPLE_RATE;
+use crate::effects::{self, Effect};
+
+/// Ring modulator: multiplies the signal by a sine carrier
+pub struct RingMod {
+    phase: f64,
+    freq: SmoothedParam, // Hz
+    mix: SmoothedParam,
+    bypassed: bool,
+}
+
+impl RingMod {
+    pub fn new() -> Self {
+        Self {
+            phase: 0.0,
+            freq: SmoothedParam::new(440.0),
+            mix: SmoothedParam::new(0.5),
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 2] = [(20.0, 2000.0), (0.0, 1.0)];
+
+impl Effect for RingMod {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mix = self.mix.tick();
+            let carrier = (self.phase * std::f64::consts::TAU).sin() as f32;
+            self.phase += self.freq.tick() as f64 / SAMPLE_RATE as f64;
+            if self.phase >= 1.0 { self.phase -= 1.0; }
+
+            *l = *l * (1.0 - mix) + *l * carrier * mix;
+            *r = *r * (1.0 - mix) + *r * carrier * mix;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.freq.set(value),
+            1 => self.mix.set(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.freq.target(),
+            1 => self.mix.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "FREQ", 1 => "MIX", _ => "" }
+    }
+    fn name(&self) -> &str { "RINGMOD" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn settle(&mut self) {
+        self.freq.settle();
+        self.mix.settle();
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::effects::{self, Effect, EFFECTS};
+
+/// Saved state of one effect: which effect it is, its parameters and bypass.
+/// Values use the same units as `Effect::set_param`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EffectSnapshot {
+    /// Effect name as listed in `EFFECTS`
+    pub effect: String,
+    /// Parameter values, in `param_name` order
+    pub params: Vec<f32>,
+    #[serde(default)]
+    pub bypassed: bool,
+}
+
+impl EffectSnapshot {
+    pub fn capture(effect: &dyn Effect) -> Self {
+        Self {
+            effect: effect.name().to_string(),
+            params: effects::param_values(effect),
+            bypassed: effect.is_bypassed(),
+        }
+    }
+
+    /// Build a snapshot of the registry effect at `index` from its parameter values
+    pub fn from_values(index: usize, values: &[f32], bypassed: bool) -> Option<Self> {
+        EFFECTS.get(index).map(|(name, _)| Self {
+            effect: name.to_string(),
+            params: values.to_vec(),
+            bypassed,
+        })
+    }
+
+    pub fn effect_index(&self) -> Option<usize> {
+        effects::effect_index(&self.effect)
+    }
+
+    /// Create the effect with the saved settings, already in place rather than
+    /// ramping. Missing parameters keep their defaults and out-of-range values
+    /// are clamped by the effect itself.
+    pub fn restore(&self) -> Option<Box<dyn Effect>> {
+        let mut effect = effects::create_effect(self.effect_index()?)?;
+        for (index, value) in self.params.iter().enumerate().take(effect.param_count()) {
+            effect.set_param(index, *value);
+        }
+        effect.set_bypass(self.bypassed);
+        effect.settle();
+        Some(effect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_round_trips_through_json() {
+        let mut delay = effects::create_effect(1).unwrap();
+        delay.set_param(1, 0.7);
+        delay.set_param(3, 6.0);
+        delay.set_bypass(true);
+
+        let json = serde_json::to_string(&EffectSnapshot::capture(delay.as_ref())).unwrap();
+        let snapshot: EffectSnapshot = serde_json::from_str(&json).unwrap();
+        let restored = snapshot.restore().unwrap();
+
+        assert_eq!(restored.name(), "DELAY");
+        assert_eq!(effects::param_values(restored.as_ref()), effects::param_values(delay.as_ref()));
+        assert!(restored.is_bypassed());
+    }
+}
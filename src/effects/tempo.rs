@@ -0,0 +1,103 @@
+use crate::constants::SAMPLE_RATE;
+
+/// Tempo and transport state handed to effects before each block
+#[derive(Debug, Clone, Copy)]
+pub struct TempoInfo {
+    pub bpm: f32,
+    /// Quarter notes per bar, from the time signature
+    pub bar_beats: f64,
+    /// Quarter notes elapsed at the start of the block
+    pub beat_position: f64,
+    /// Whether the tape is moving
+    pub playing: bool,
+}
+
+impl TempoInfo {
+    pub fn new(bpm: f32) -> Self {
+        Self { bpm, bar_beats: 4.0, beat_position: 0.0, playing: false }
+    }
+
+    pub fn beat_samples(&self) -> f64 {
+        60.0 / self.bpm as f64 * SAMPLE_RATE as f64
+    }
+}
+
+/// Note divisions for synced delay times, in quarter notes; index 0 means free time
+pub const NOTE_DIVISIONS: [(&str, f64); 13] = [
+    ("FREE", 0.0),
+    ("1/32", 0.125),
+    ("1/16T", 1.0 / 6.0),
+    ("1/16", 0.25),
+    ("1/8T", 1.0 / 3.0),
+    ("1/16D", 0.375),
+    ("1/8", 0.5),
+    ("1/4T", 2.0 / 3.0),
+    ("1/8D", 0.75),
+    ("1/4", 1.0),
+    ("1/4D", 1.5),
+    ("1/2", 2.0),
+    ("1/1", 4.0),
+];
+
+/// LFO cycle lengths in bars for synced modulation; index 0 means free rate in Hz
+pub const BAR_CYCLES: [(&str, f64); 7] = [
+    ("FREE", 0.0),
+    ("1/8 BAR", 0.125),
+    ("1/4 BAR", 0.25),
+    ("1/2 BAR", 0.5),
+    ("1 BAR", 1.0),
+    ("2 BARS", 2.0),
+    ("4 BARS", 4.0),
+];
+
+/// Decode a sync parameter into an index of `table`, where 0 means not synced
+pub fn sync_index(value: f32, table_len: usize) -> usize {
+    (value.round().max(0.0) as usize).min(table_len - 1)
+}
+
+/// Synced LFO state: phase locked to the bar grid when synced, free-running otherwise
+pub struct SyncedLfo {
+    phase: f64,
+    rate: f32, // Hz, used when free
+    pub sync: usize,
+    increment: f64,
+}
+
+impl SyncedLfo {
+    pub fn new(rate: f32) -> Self {
+        Self { phase: 0.0, rate, sync: 0, increment: rate as f64 / SAMPLE_RATE as f64 }
+    }
+
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+        if self.sync == 0 {
+            self.increment = rate as f64 / SAMPLE_RATE as f64;
+        }
+    }
+
+    /// Lock the phase to the transport when synced, and set the per-sample increment
+    pub fn set_transport(&mut self, tempo: &TempoInfo) {
+        let bars = BAR_CYCLES[self.sync].1;
+        if bars > 0.0 {
+            let cycle_beats = bars * tempo.bar_beats;
+            self.phase = (tempo.beat_position / cycle_beats).fract();
+            self.increment = 1.0 / (cycle_beats * tempo.beat_samples());
+        } else {
+            self.increment = self.rate as f64 / SAMPLE_RATE as f64;
+        }
+    }
+
+    /// Current phase in cycles (0..1), then advance one sample
+    pub fn tick(&mut self) -> f64 {
+        let phase = self.phase;
+        self.phase += self.increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+        phase
+    }
+}
@@ -0,0 +1,88 @@
+use crate::audio::smoothing::SmoothedParam;
+use crate::effects::tempo::{self, SyncedLfo, TempoInfo, BAR_CYCLES};
+use crate::effects::{self, Effect};
+
+/// Amplitude modulation, from a smooth sine to a choppy square
+pub struct Tremolo {
+    lfo: SyncedLfo,
+    depth: SmoothedParam,
+    shape: SmoothedParam,
+    bypassed: bool,
+}
+
+impl Tremolo {
+    pub fn new() -> Self {
+        Self {
+            lfo: SyncedLfo::new(4.0),
+            depth: SmoothedParam::new(0.5),
+            shape: SmoothedParam::new(0.0),
+            bypassed: false,
+        }
+    }
+}
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 4] = [
+    (0.1, 20.0),
+    (0.0, 1.0),
+    (0.0, 1.0),
+    (0.0, (BAR_CYCLES.len() - 1) as f32),
+];
+
+impl Effect for Tremolo {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        // Harder drive squares off the sine
+        let drive_for = |shape: f32| 1.0 + shape * 20.0;
+        let mut drive = drive_for(self.shape.value());
+        let mut norm = drive.tanh();
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            if self.shape.is_smoothing() {
+                self.shape.tick();
+                drive = drive_for(self.shape.value());
+                norm = drive.tanh();
+            }
+            let sine = (self.lfo.tick() * std::f64::consts::TAU).sin() as f32;
+            let wave = (sine * drive).tanh() / norm;
+            let gain = 1.0 - self.depth.tick() * (0.5 - 0.5 * wave);
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.lfo.set_rate(value),
+            1 => self.depth.set(value),
+            2 => self.shape.set(value),
+            3 => self.lfo.sync = tempo::sync_index(value, BAR_CYCLES.len()),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.lfo.rate(),
+            1 => self.depth.target(),
+            2 => self.shape.target(),
+            3 => self.lfo.sync as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "RATE", 1 => "DEPTH", 2 => "SHAPE", 3 => "SYNC", _ => "" }
+    }
+    fn name(&self) -> &str { "TREMOLO" }
+    fn set_bypass(&mut self, bypass: bool) { self.bypassed = bypass; }
+    fn is_bypassed(&self) -> bool { self.bypassed }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.lfo.set_transport(tempo); }
+    fn settle(&mut self) {
+        self.depth.settle();
+        self.shape.settle();
+    }
+}
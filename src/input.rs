@@ -1,10 +1,11 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::app::AppMode;
+use crate::constants::TRACK_COUNT;
 use crate::messages::UiEvent;
 
 /// Map keyboard input to UiEvent based on current mode
-pub fn handle_key(key: KeyEvent, mode: AppMode, selected_track: usize) -> Option<UiEvent> {
+pub fn handle_key(key: KeyEvent, mode: AppMode, selected_track: usize, drum_perform: bool) -> Option<UiEvent> {
     // Global keys (all modes)
     match key.code {
         // Quit: Esc always works. Q quits except in Synth mode (where it's a piano key).
@@ -25,15 +26,15 @@ pub fn handle_key(key: KeyEvent, mode: AppMode, selected_track: usize) -> Option
         KeyCode::Tab => return Some(UiEvent::CycleMode),
         // Stop + rewind to start
         KeyCode::Enter => return Some(UiEvent::StopTransport),
-        // Cycle recording source: INT → SYNTH → DRUM → MIC → ALL
+        // Cycle recording source: INT → SYNTH → DRUM → KICK…RIM → MIC → ALL
         KeyCode::Char('i') => return Some(UiEvent::CycleRecordSource),
         _ => {}
     }
 
-    // Track selection (1-4) only in Tape and Mixer modes
+    // Track selection (1-4) only in Tape, Notes and Mixer modes
     match key.code {
         KeyCode::Char(c @ '1'..='4')
-            if mode == AppMode::Tape || mode == AppMode::Mixer =>
+            if matches!(mode, AppMode::Tape | AppMode::Notes | AppMode::Mixer) =>
         {
             let track = (c as usize) - ('1' as usize);
             return Some(UiEvent::SelectTrack(track));
@@ -44,8 +45,9 @@ pub fn handle_key(key: KeyEvent, mode: AppMode, selected_track: usize) -> Option
     // Mode-specific keys
     match mode {
         AppMode::Tape => handle_tape_key(key, selected_track),
-        AppMode::Synth => handle_synth_key(key),
-        AppMode::Drum => handle_drum_key(key),
+        AppMode::Synth => handle_synth_key(key, selected_track),
+        AppMode::Notes => handle_notes_key(key),
+        AppMode::Drum => handle_drum_key(key, drum_perform),
         AppMode::Mixer => handle_mixer_key(key, selected_track),
     }
 }
@@ -64,7 +66,7 @@ fn handle_tape_key(key: KeyEvent, selected_track: usize) -> Option<UiEvent> {
     }
 }
 
-fn handle_synth_key(key: KeyEvent) -> Option<UiEvent> {
+fn handle_synth_key(key: KeyEvent, selected_track: usize) -> Option<UiEvent> {
     // R = record (not a piano key — use Synth mode to record synth to tape)
     if key.code == KeyCode::Char('r') {
         return Some(UiEvent::StartRecord);
@@ -110,13 +112,54 @@ fn handle_synth_key(key: KeyEvent) -> Option<UiEvent> {
     match key.code {
         KeyCode::Left => Some(UiEvent::SelectEngine(0)),   // prev
         KeyCode::Right => Some(UiEvent::SelectEngine(1)),  // next
-        KeyCode::Up => Some(UiEvent::SetParam(0, 0.05)),   // increment
-        KeyCode::Down => Some(UiEvent::SetParam(0, -0.05)), // decrement
+        KeyCode::Char('[') => Some(UiEvent::SelectParam(0)), // prev
+        KeyCode::Char(']') => Some(UiEvent::SelectParam(1)), // next
+        KeyCode::Up => Some(UiEvent::AdjustParam(0.05)),   // increment
+        KeyCode::Down => Some(UiEvent::AdjustParam(-0.05)), // decrement
+        KeyCode::Char('o') => Some(UiEvent::BrowsePreset(0)), // prev
+        KeyCode::Char('p') => Some(UiEvent::BrowsePreset(1)), // next
+        KeyCode::Char('P') => Some(UiEvent::SavePreset),
+        KeyCode::Char('a') => Some(UiEvent::ToggleArp),
+        KeyCode::Char('f') => Some(UiEvent::CycleArpMode),
+        KeyCode::Char('1') => Some(UiEvent::CycleArpOctaves),
+        KeyCode::Char('8') => Some(UiEvent::CycleArpRate),
+        KeyCode::Char('9') => Some(UiEvent::AdjustArpGate(-0.1)),
+        KeyCode::Char('0') => Some(UiEvent::AdjustArpGate(0.1)),
+        KeyCode::Char('k') => Some(UiEvent::ToggleArpLatch),
+        KeyCode::Char(';') => Some(UiEvent::SampleTrack),
+        KeyCode::Char('\'') => Some(UiEvent::CycleSampleSource),
+        KeyCode::Char('/') => Some(UiEvent::LoadSampleFile),
+        // Number keys are piano keys here, so step through tracks instead
+        KeyCode::Char(',') => Some(UiEvent::SelectTrack((selected_track + TRACK_COUNT - 1) % TRACK_COUNT)),
+        KeyCode::Char('.') => Some(UiEvent::SelectTrack((selected_track + 1) % TRACK_COUNT)),
         _ => None,
     }
 }
 
-fn handle_drum_key(key: KeyEvent) -> Option<UiEvent> {
+fn handle_notes_key(key: KeyEvent) -> Option<UiEvent> {
+    let octave = key.modifiers.contains(KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Left => Some(UiEvent::MoveNoteCursor(-1, 0)),
+        KeyCode::Right => Some(UiEvent::MoveNoteCursor(1, 0)),
+        KeyCode::Up => Some(UiEvent::MoveNoteCursor(0, if octave { 12 } else { 1 })),
+        KeyCode::Down => Some(UiEvent::MoveNoteCursor(0, if octave { -12 } else { -1 })),
+        KeyCode::Char('[') => Some(UiEvent::MoveNoteCursor(-16, 0)), // prev bar
+        KeyCode::Char(']') => Some(UiEvent::MoveNoteCursor(16, 0)),  // next bar
+        KeyCode::Char('x') => Some(UiEvent::ToggleNote),
+        KeyCode::Char('-') => Some(UiEvent::AdjustNoteLength(-1)),
+        KeyCode::Char('=') => Some(UiEvent::AdjustNoteLength(1)),
+        KeyCode::Char(',') => Some(UiEvent::AdjustNoteVelocity(-0.05)),
+        KeyCode::Char('.') => Some(UiEvent::AdjustNoteVelocity(0.05)),
+        KeyCode::Char('a') => Some(UiEvent::AddNoteBar),
+        KeyCode::Char('d') => Some(UiEvent::RemoveNoteBar),
+        KeyCode::Char('c') => Some(UiEvent::ClearNotes),
+        KeyCode::Char('u') => Some(UiEvent::UndoClearNotes),
+        KeyCode::Char('r') => Some(UiEvent::StartRecord),
+        _ => None,
+    }
+}
+
+fn handle_drum_key(key: KeyEvent, perform: bool) -> Option<UiEvent> {
     // Instrument selection: 1-6
     match key.code {
         KeyCode::Char('1') => return Some(UiEvent::SelectInstrument(0)),
@@ -128,32 +171,76 @@ fn handle_drum_key(key: KeyEvent) -> Option<UiEvent> {
         _ => {}
     }
 
-    // Z-K row toggles steps 0-15
-    let step = match key.code {
-        KeyCode::Char('z') => Some(0usize),
-        KeyCode::Char('x') => Some(1),
-        KeyCode::Char('c') => Some(2),
-        KeyCode::Char('v') => Some(3),
-        KeyCode::Char('b') => Some(4),
-        KeyCode::Char('n') => Some(5),
-        KeyCode::Char('m') => Some(6),
-        KeyCode::Char(',') => Some(7),
-        KeyCode::Char('a') => Some(8),
-        KeyCode::Char('s') => Some(9),
-        KeyCode::Char('d') => Some(10),
-        KeyCode::Char('f') => Some(11),
-        KeyCode::Char('g') => Some(12),
-        KeyCode::Char('h') => Some(13),
-        KeyCode::Char('j') => Some(14),
-        KeyCode::Char('k') => Some(15),
-        _ => None,
-    };
+    // Performance mode: Z-N and A-H play the instruments
+    if perform {
+        const PAD_KEYS: [char; 12] = ['z', 'x', 'c', 'v', 'b', 'n', 'a', 's', 'd', 'f', 'g', 'h'];
+        if let KeyCode::Char(c) = key.code {
+            if let Some(inst) = PAD_KEYS.iter().position(|k| *k == c) {
+                return Some(UiEvent::PlayDrum(inst % 6));
+            }
+        }
+    }
 
-    if let Some(s) = step {
-        return Some(UiEvent::ToggleStep(0, s)); // instrument selected separately
+    // Z-K row toggles steps 0-15; shifted, it selects the step for editing
+    const STEP_KEYS: [char; 16] = [
+        'z', 'x', 'c', 'v', 'b', 'n', 'm', ',', 'a', 's', 'd', 'f', 'g', 'h', 'j', 'k',
+    ];
+    if let KeyCode::Char(c) = key.code {
+        if let Some(s) = STEP_KEYS.iter().position(|k| *k == c) {
+            // Step keys without a pad do nothing while performing, so playing can't edit the pattern
+            if perform {
+                return None;
+            }
+            return Some(UiEvent::ToggleStep(0, s)); // instrument selected separately
+        }
+        let shifted = if c == '<' { ',' } else { c.to_ascii_lowercase() };
+        if shifted != c {
+            if let Some(s) = STEP_KEYS.iter().position(|k| *k == shifted) {
+                return Some(UiEvent::SelectStep(s));
+            }
+        }
     }
 
+    // Shift+arrows set the groove
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
     match key.code {
+        KeyCode::Char(';') => Some(UiEvent::CycleDrumKit),
+        KeyCode::Char('o') => Some(UiEvent::SelectPattern(0)), // prev
+        KeyCode::Char('p') => Some(UiEvent::SelectPattern(1)), // next
+        KeyCode::Char('y') => Some(UiEvent::CopyPattern),
+        KeyCode::Char('u') => Some(UiEvent::PastePattern),
+        KeyCode::Backspace => Some(UiEvent::ClearPattern),
+        KeyCode::Char('w') => Some(UiEvent::ToggleSongMode),
+        KeyCode::Char('e') => Some(UiEvent::AppendSong),
+        KeyCode::Char('t') => Some(UiEvent::RemoveSong),
+        KeyCode::Left if shift => Some(UiEvent::AdjustSwing(-0.01)),
+        KeyCode::Right if shift => Some(UiEvent::AdjustSwing(0.01)),
+        KeyCode::Up if shift => Some(UiEvent::AdjustHumanize(0.05)),
+        KeyCode::Down if shift => Some(UiEvent::AdjustHumanize(-0.05)),
+        KeyCode::Char('(') => Some(UiEvent::NudgeStep(-0.05)),
+        KeyCode::Char(')') => Some(UiEvent::NudgeStep(0.05)),
+        KeyCode::Left => Some(UiEvent::SelectStepPage(0)), // prev
+        KeyCode::Right => Some(UiEvent::SelectStepPage(1)), // next
+        KeyCode::Char('{') => Some(UiEvent::AdjustPatternLength(-1)),
+        KeyCode::Char('}') => Some(UiEvent::AdjustPatternLength(1)),
+        KeyCode::Char('_') => Some(UiEvent::AdjustLaneLength(-1)),
+        KeyCode::Char('+') => Some(UiEvent::AdjustLaneLength(1)),
+        KeyCode::Char('7') => Some(UiEvent::AdjustStepVelocity(-0.1)),
+        KeyCode::Char('8') => Some(UiEvent::AdjustStepVelocity(0.1)),
+        KeyCode::Char('9') => Some(UiEvent::AdjustStepProbability(-0.1)),
+        KeyCode::Char('0') => Some(UiEvent::AdjustStepProbability(0.1)),
+        KeyCode::Char('/') => Some(UiEvent::CycleRatchets),
+        KeyCode::Char('.') => Some(UiEvent::ToggleDrumMute),
+        KeyCode::Char('`') => Some(UiEvent::TogglePerformMode),
+        KeyCode::Char('~') => Some(UiEvent::CycleQuantize),
+        KeyCode::Char('\'') => Some(UiEvent::ToggleLockMode),
+        KeyCode::Char('"') => Some(UiEvent::ClearLocks),
+        KeyCode::Char('\\') => Some(UiEvent::CycleResolution),
+        KeyCode::Char('|') => Some(UiEvent::CycleTimeSignature),
+        KeyCode::Char('[') => Some(UiEvent::SelectDrumParam(0)), // prev
+        KeyCode::Char(']') => Some(UiEvent::SelectDrumParam(1)), // next
+        KeyCode::Char('-') => Some(UiEvent::AdjustDrumParam(false)),
+        KeyCode::Char('=') => Some(UiEvent::AdjustDrumParam(true)),
         KeyCode::Up => Some(UiEvent::SetBpm(1.0)),
         KeyCode::Down => Some(UiEvent::SetBpm(-1.0)),
         KeyCode::Char('r') => Some(UiEvent::StartRecord),
@@ -169,6 +256,27 @@ fn handle_mixer_key(key: KeyEvent, selected_track: usize) -> Option<UiEvent> {
         KeyCode::Right => Some(UiEvent::SetPan(selected_track, 0.1)),
         KeyCode::Char('m') => Some(UiEvent::MuteTrack(selected_track)),
         KeyCode::Char('s') => Some(UiEvent::SoloTrack(selected_track)),
+        KeyCode::Char('[') => Some(UiEvent::SelectSend(0)),
+        KeyCode::Char(']') => Some(UiEvent::SelectSend(1)),
+        KeyCode::Char('-') => Some(UiEvent::AdjustSend(selected_track, -0.05)),
+        KeyCode::Char('=') => Some(UiEvent::AdjustSend(selected_track, 0.05)),
+        KeyCode::Char('p') => Some(UiEvent::ToggleSendPre(selected_track)),
+        KeyCode::Char(',') => Some(UiEvent::SelectMasterSlot(0)),
+        KeyCode::Char('.') => Some(UiEvent::SelectMasterSlot(1)),
+        KeyCode::Char('<') => Some(UiEvent::MoveMasterSlot(0)),
+        KeyCode::Char('>') => Some(UiEvent::MoveMasterSlot(1)),
+        KeyCode::Char('b') => Some(UiEvent::ToggleMasterSlot),
+        KeyCode::Char('e') => Some(UiEvent::CycleEqBand),
+        KeyCode::Char('(') => Some(UiEvent::AdjustEq(selected_track, -1.0)),
+        KeyCode::Char(')') => Some(UiEvent::AdjustEq(selected_track, 1.0)),
+        KeyCode::Char('f') => Some(UiEvent::SelectFxSlot),
+        KeyCode::Char('g') => Some(UiEvent::CycleEffect(0)),
+        KeyCode::Char('h') => Some(UiEvent::CycleEffect(1)),
+        KeyCode::Char('x') => Some(UiEvent::ToggleEffect),
+        KeyCode::Char('j') => Some(UiEvent::SelectFxParam(0)),
+        KeyCode::Char('k') => Some(UiEvent::SelectFxParam(1)),
+        KeyCode::Char('9') => Some(UiEvent::AdjustFxParam(0)),
+        KeyCode::Char('0') => Some(UiEvent::AdjustFxParam(1)),
         _ => None,
     }
 }
@@ -199,13 +307,36 @@ pub fn key_hints(mode: AppMode) -> Vec<(&'static str, &'static str)> {
             hints.insert(0, ("Z-M", "Play"));
             hints.insert(1, ("R", "Record"));
             hints.insert(2, ("←/→", "Engine"));
-            hints.insert(3, ("↑/↓", "Param"));
+            hints.insert(3, ("[/]", "Param"));
+            hints.insert(4, ("↑/↓", "Adjust"));
+            hints.insert(5, ("O/P", "Patch"));
+            hints.insert(6, ("Shift+P", "Save Patch"));
+            hints.insert(7, (",/.", "Track"));
+            hints.insert(8, ("A", "Arp"));
+            hints.insert(9, (";", "Sample"));
+        }
+        AppMode::Notes => {
+            hints.insert(0, ("1-4", "Track"));
+            hints.insert(1, ("Arrows", "Cursor"));
+            hints.insert(2, ("X", "Note"));
+            hints.insert(3, ("-/=", "Length"));
+            hints.insert(4, (",/.", "Velocity"));
+            hints.insert(5, ("[/]", "Bar"));
+            hints.insert(6, ("A/D", "Add/Del Bar"));
+            hints.insert(7, ("C", "Clear"));
+            hints.insert(8, ("U", "Undo Clear"));
         }
         AppMode::Drum => {
             hints.insert(0, ("Z-K", "Steps"));
             hints.insert(1, ("1-6", "Inst"));
             hints.insert(2, ("↑/↓", "BPM"));
             hints.insert(3, ("R", "Record"));
+            hints.insert(4, ("[/]", "Param"));
+            hints.insert(5, ("-/=", "Adjust"));
+            hints.insert(6, ("O/P", "Pattern"));
+            hints.insert(7, ("←/→", "Page"));
+            hints.insert(8, ("W", "Song"));
+            hints.insert(9, (";", "Kit"));
         }
         AppMode::Mixer => {
             hints.insert(0, ("1-4", "Track"));
@@ -213,6 +344,18 @@ pub fn key_hints(mode: AppMode) -> Vec<(&'static str, &'static str)> {
             hints.insert(2, ("←/→", "Pan"));
             hints.insert(3, ("M", "Mute"));
             hints.insert(4, ("S", "Solo"));
+            hints.insert(5, ("[/]", "Send"));
+            hints.insert(6, ("-/=", "Send Lvl"));
+            hints.insert(7, ("P", "Pre/Post"));
+            hints.insert(8, (",/.", "Master FX"));
+            hints.insert(9, ("B", "Bypass"));
+            hints.insert(10, ("E", "EQ Band"));
+            hints.insert(11, ("(/)", "EQ Gain"));
+            hints.insert(12, ("F", "FX Slot"));
+            hints.insert(13, ("G/H", "Effect"));
+            hints.insert(14, ("X", "FX Bypass"));
+            hints.insert(15, ("J/K", "FX Param"));
+            hints.insert(16, ("9/0", "FX Value"));
         }
     }
 
@@ -30,9 +30,16 @@ use crate::app::{AppMode, AppState};
 use crate::audio::buffer::{downsample_track, SharedBuffers};
 use crate::audio::engine::AudioEngine;
 use crate::constants::*;
+use crate::effects::eq::{CHANNEL_EQ_BANDS, CHANNEL_EQ_RANGE};
+use crate::effects::snapshot::EffectSnapshot;
 use crate::messages::*;
+use crate::project::metadata::MasterInsertMeta;
+use crate::sequencer::groove;
+use crate::sequencer::pattern::{DrumPattern, Step};
+use crate::synth::patch::Patch;
 use crate::ui::views::drum_view::DrumView;
 use crate::ui::views::mixer_view::MixerView;
+use crate::ui::views::notes_view::NotesView;
 use crate::ui::views::synth_view::SynthView;
 use crate::ui::views::tape_view::TapeView;
 use crate::ui::views::View;
@@ -77,13 +84,16 @@ fn run_ui_loop(
 
     // --- App state ---
     let mut state = AppState::new();
+    if let Some(dir) = synth::patch::presets_dir() {
+        state.presets.extend(synth::patch::load_user_presets(&dir));
+    }
     let mut tape_view = TapeView::new();
 
     let frame_duration = Duration::from_millis(1000 / UI_FPS);
 
-    // Auto-release for synth notes: track note -> press time
+    // Auto-release for synth notes: (track, note) -> press time
     // Most terminals don't support KeyEventKind::Release, so we auto-release after 200ms
-    let mut active_notes: HashMap<u8, Instant> = HashMap::new();
+    let mut active_notes: HashMap<(usize, u8), Instant> = HashMap::new();
     let note_duration = Duration::from_millis(200);
 
     // --- Main loop ---
@@ -94,10 +104,43 @@ fn run_ui_loop(
         while let Ok(msg) = audio_msg_rx.try_recv() {
             match msg {
                 AudioMsg::Position(pos) => state.position = pos,
-                AudioMsg::CurrentStep(step) => state.current_step = step,
+                AudioMsg::CurrentPattern(pattern) => {
+                    state.playing_pattern = pattern;
+                    if state.queued_pattern == Some(pattern) {
+                        state.queued_pattern = None;
+                    }
+                }
+                AudioMsg::SongEntry(entry) => state.song_entry = entry,
+                AudioMsg::CurrentStep(step) => state.seq_step = step,
+                AudioMsg::DrumStep(step) => state.current_step = step,
+                AudioMsg::StepRecorded(pattern, inst, step, nudge) => {
+                    let lane = state.pattern_bank.patterns.get_mut(pattern).and_then(|p| p.lanes.get_mut(inst));
+                    if let Some(settings) = lane.and_then(|l| l.step_mut(step)) {
+                        settings.active = true;
+                        settings.set_nudge(nudge);
+                    }
+                }
                 AudioMsg::Levels(levels) => state.levels = levels,
                 AudioMsg::Peaks(peaks) => state.peaks = peaks,
                 AudioMsg::MasterLevel(l, r) => state.master_level = (l, r),
+                AudioMsg::MasterGainReduction(gr) => state.master_gr = gr,
+                AudioMsg::InsertGainReduction(gr) => state.insert_gr = gr,
+                AudioMsg::MasterChain(chain) => {
+                    state.tape_sim_enabled =
+                        chain.iter().any(|(insert, bypassed)| MASTER_INSERTS[*insert] == "TAPE" && !bypassed);
+                    state.master_chain = chain;
+                }
+                AudioMsg::SynthParams(track, engine, values) => {
+                    if let Some(params) = state
+                        .instruments
+                        .get_mut(track)
+                        .and_then(|inst| inst.params.get_mut(engine))
+                    {
+                        *params = values.as_slice().to_vec();
+                    }
+                }
+                // Dropped here, off the audio thread
+                AudioMsg::Retired(_) => {}
             }
         }
 
@@ -105,10 +148,10 @@ fn run_ui_loop(
         if event::poll(Duration::from_millis(1))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
-                    if let Some(evt) = input::handle_key(key, state.mode, state.selected_track) {
+                    if let Some(evt) = input::handle_key(key, state.mode, state.selected_track, state.drum_perform) {
                         // Track NoteOn events for auto-release
                         if let UiEvent::NoteOn(note, _) = &evt {
-                            active_notes.insert(*note, Instant::now());
+                            active_notes.insert((state.selected_track, *note), Instant::now());
                         }
                         handle_ui_event(&mut state, evt, &audio_cmd_tx, &buffers);
                     }
@@ -117,14 +160,14 @@ fn run_ui_loop(
         }
 
         // --- Auto-release synth notes after duration ---
-        let expired: Vec<u8> = active_notes
+        let expired: Vec<(usize, u8)> = active_notes
             .iter()
             .filter(|(_, pressed_at)| pressed_at.elapsed() >= note_duration)
-            .map(|(note, _)| *note)
+            .map(|(key, _)| *key)
             .collect();
-        for note in expired {
-            active_notes.remove(&note);
-            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOff(note));
+        for (track, note) in expired {
+            active_notes.remove(&(track, note));
+            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOff(track, note));
         }
 
         if state.should_quit {
@@ -132,7 +175,7 @@ fn run_ui_loop(
         }
 
         // --- Update waveform data periodically ---
-        if tape_view.frame_count % 30 == 0 {
+        if tape_view.frame_count.is_multiple_of(30) {
             if let Ok(bufs) = buffers.try_lock() {
                 for i in 0..TRACK_COUNT {
                     state.waveform_data[i] = downsample_track(&bufs.tracks[i], 200);
@@ -158,6 +201,7 @@ fn run_ui_loop(
             match state.mode {
                 AppMode::Tape => tape_view.render(&state, frame, layout.main),
                 AppMode::Synth => SynthView.render(&state, frame, layout.main),
+                AppMode::Notes => NotesView.render(&state, frame, layout.main),
                 AppMode::Drum => DrumView.render(&state, frame, layout.main),
                 AppMode::Mixer => MixerView.render(&state, frame, layout.main),
             }
@@ -220,11 +264,13 @@ fn handle_ui_event(
             state.transport = TransportDisplay::Stopped;
             state.position = 0;
             state.current_step = 0;
+            state.seq_step = 0;
             let _ = audio_cmd_tx.try_send(AudioCmd::Stop);
         }
         UiEvent::SelectTrack(track) => {
             if track < TRACK_COUNT {
                 state.selected_track = track;
+                state.note_cursor = state.note_cursor.min(state.note_pattern().len() - 1);
             }
         }
         UiEvent::ArmTrack(track) => {
@@ -283,26 +329,227 @@ fn handle_ui_event(
                 let _ = audio_cmd_tx.try_send(AudioCmd::SetPan(track, new_pan));
             }
         }
+        UiEvent::SelectSend(dir) => {
+            state.selected_send = if dir == 0 {
+                (state.selected_send + AUX_COUNT - 1) % AUX_COUNT
+            } else {
+                (state.selected_send + 1) % AUX_COUNT
+            };
+        }
+        UiEvent::AdjustSend(track, delta) => {
+            if track < TRACK_COUNT {
+                let aux = state.selected_send;
+                let send = &mut state.track_displays[track].sends[aux];
+                *send = (*send + delta).clamp(0.0, 1.0);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetSend(track, aux, *send));
+            }
+        }
+        UiEvent::CycleEqBand => {
+            state.selected_eq_band = (state.selected_eq_band + 1) % CHANNEL_EQ_BANDS;
+        }
+        UiEvent::AdjustEq(track, delta) => {
+            if track < TRACK_COUNT {
+                let band = state.selected_eq_band;
+                let gain = &mut state.track_displays[track].eq[band];
+                *gain = (*gain + delta).clamp(-CHANNEL_EQ_RANGE, CHANNEL_EQ_RANGE);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetChannelEq(track, band, *gain));
+            }
+        }
+        UiEvent::ToggleSendPre(track) => {
+            if track < TRACK_COUNT {
+                let aux = state.selected_send;
+                let pre = &mut state.track_displays[track].send_pre[aux];
+                *pre = !*pre;
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetSendPre(track, aux, *pre));
+            }
+        }
         UiEvent::NoteOn(note, vel) => {
-            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOn(note, vel));
+            let track = state.selected_track;
+            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOn(track, note, vel));
         }
         UiEvent::NoteOff(note) => {
-            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOff(note));
+            let track = state.selected_track;
+            let _ = audio_cmd_tx.try_send(AudioCmd::NoteOff(track, note));
         }
         UiEvent::SelectEngine(dir) => {
+            let track = state.selected_track;
             let count = synth::engines::ENGINE_COUNT;
+            let inst = &mut state.instruments[track];
             if dir == 0 {
-                state.synth_engine = (state.synth_engine + count - 1) % count;
+                inst.engine = (inst.engine + count - 1) % count;
             } else {
-                state.synth_engine = (state.synth_engine + 1) % count;
+                inst.engine = (inst.engine + 1) % count;
+            }
+            inst.patch_name = "INIT".to_string();
+            let count = inst.engine_params().len();
+            state.selected_param = state.selected_param.min(count.saturating_sub(1));
+            send_instrument(state, track, audio_cmd_tx);
+        }
+        UiEvent::SelectParam(dir) => {
+            let count = state.instrument().engine_params().len();
+            if count > 0 {
+                state.selected_param = if dir == 0 {
+                    (state.selected_param + count - 1) % count
+                } else {
+                    (state.selected_param + 1) % count
+                };
+            }
+        }
+        UiEvent::AdjustParam(delta) => {
+            let track = state.selected_track;
+            let index = state.selected_param;
+            let inst = &mut state.instruments[track];
+            if let Some(value) = inst.params[inst.engine].get_mut(index) {
+                *value = (*value + delta).clamp(0.0, 1.0);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetParam(track, index, *value));
+            }
+        }
+        UiEvent::BrowsePreset(dir) => {
+            let count = state.presets.len();
+            if count > 0 {
+                let next = match (state.selected_preset, dir) {
+                    (None, 0) => count - 1,
+                    (None, _) => 0,
+                    (Some(i), 0) => (i + count - 1) % count,
+                    (Some(i), _) => (i + 1) % count,
+                };
+                state.selected_preset = Some(next);
+                let patch = state.presets[next].clone();
+                apply_patch(state, state.selected_track, &patch, audio_cmd_tx);
+            }
+        }
+        UiEvent::SavePreset => {
+            let track = state.selected_track;
+            let engine = synth::engines::ENGINE_NAMES[state.instruments[track].engine];
+            let name = (1..)
+                .map(|n| format!("{} {}", engine, n))
+                .find(|name| !state.presets.iter().any(|p| &p.name == name))
+                .unwrap_or_default();
+            state.instruments[track].patch_name = name;
+            let patch = state.instruments[track].patch();
+            match synth::patch::presets_dir() {
+                Some(dir) => match synth::patch::save_patch(&dir, &patch) {
+                    Ok(_) => {
+                        state.presets.push(patch);
+                        state.selected_preset = Some(state.presets.len() - 1);
+                    }
+                    Err(e) => eprintln!("Preset save error: {}", e),
+                },
+                None => eprintln!("Preset save error: no config directory"),
+            }
+        }
+        UiEvent::MoveNoteCursor(steps, semitones) => {
+            let len = state.note_pattern().len() as i32;
+            state.note_cursor = (state.note_cursor as i32 + steps).clamp(0, len - 1) as usize;
+            state.note_pitch = (state.note_pitch as i32 + semitones).clamp(0, 127) as u8;
+        }
+        UiEvent::ToggleNote => {
+            let (step, note) = (state.note_cursor, state.note_pitch);
+            state.note_patterns[state.selected_track].toggle(step, note, 0.8);
+            send_note_pattern(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::AdjustNoteLength(delta) => {
+            let step = state.note_cursor;
+            state.note_patterns[state.selected_track].adjust_length(step, delta);
+            send_note_pattern(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::AdjustNoteVelocity(delta) => {
+            let step = state.note_cursor;
+            state.note_patterns[state.selected_track].adjust_velocity(step, delta);
+            send_note_pattern(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::AddNoteBar => {
+            state.note_patterns[state.selected_track].add_bar();
+            send_note_pattern(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::RemoveNoteBar => {
+            state.note_patterns[state.selected_track].remove_bar();
+            state.note_cursor = state.note_cursor.min(state.note_pattern().len() - 1);
+            send_note_pattern(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::ClearNotes => {
+            let track = state.selected_track;
+            if state.note_patterns[track].steps.iter().any(Option::is_some) {
+                state.cleared_notes[track] = Some(state.note_patterns[track].clone());
+                state.note_patterns[track].clear();
+                send_note_pattern(state, track, audio_cmd_tx);
+            }
+        }
+        UiEvent::UndoClearNotes => {
+            // Swapping keeps anything written since the clear, so undo can be undone
+            let track = state.selected_track;
+            if let Some(cleared) = state.cleared_notes[track].as_mut() {
+                std::mem::swap(cleared, &mut state.note_patterns[track]);
+                state.note_cursor = state.note_cursor.min(state.note_patterns[track].len() - 1);
+                send_note_pattern(state, track, audio_cmd_tx);
+            }
+        }
+        UiEvent::ToggleArp => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.enabled = !arp.enabled;
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::CycleArpMode => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.mode = arp.mode.next();
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::CycleArpOctaves => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.octaves = arp.octaves % 4 + 1;
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::CycleArpRate => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.rate = (arp.rate + 1) % synth::arpeggiator::ARP_RATES.len();
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::AdjustArpGate(delta) => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.gate = (arp.gate + delta).clamp(0.1, 1.0);
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::ToggleArpLatch => {
+            let arp = &mut state.instruments[state.selected_track].arp;
+            arp.latch = !arp.latch;
+            send_arp(state, state.selected_track, audio_cmd_tx);
+        }
+        UiEvent::SampleTrack => {
+            let source = state.sample_source;
+            let start = state.position;
+            let region = buffers.lock().ok().and_then(|bufs| {
+                let buffer = &bufs.tracks[source];
+                let end = buffer
+                    .sample_count()
+                    .min(start + SAMPLE_RATE as usize * MAX_SAMPLE_SECS);
+                (end > start).then(|| buffer.data[start..end].to_vec())
+            });
+            if let Some(samples) = region {
+                let name = format!("T{} {}", source + 1, state.position_display());
+                load_sample(state, state.selected_track, samples, name, audio_cmd_tx);
             }
-            let _ = audio_cmd_tx.try_send(AudioCmd::SelectEngine(state.synth_engine));
         }
-        UiEvent::SetParam(index, delta) => {
-            if index < 4 {
-                state.synth_params[index] = (state.synth_params[index] + delta).clamp(0.0, 1.0);
-                let _ =
-                    audio_cmd_tx.try_send(AudioCmd::SetParam(index, state.synth_params[index]));
+        UiEvent::CycleSampleSource => {
+            state.sample_source = (state.sample_source + 1) % TRACK_COUNT;
+        }
+        UiEvent::LoadSampleFile => {
+            let files = synth::engines::sampler::sample_files(std::path::Path::new(SAMPLES_DIR));
+            if files.is_empty() {
+                eprintln!("Sample load error: no WAV files in {}/", SAMPLES_DIR);
+                return;
+            }
+            let next = state.sample_file.map_or(0, |i| (i + 1) % files.len());
+            state.sample_file = Some(next);
+            let path = &files[next];
+            match synth::engines::sampler::load_sample_file(path) {
+                Ok(samples) => {
+                    let name = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    load_sample(state, state.selected_track, samples, name, audio_cmd_tx);
+                }
+                Err(e) => eprintln!("Sample load error: {}", e),
             }
         }
         UiEvent::ToggleStep(instrument, step) => {
@@ -311,9 +558,141 @@ fn handle_ui_event(
             } else {
                 instrument
             };
-            if inst < 6 && step < 16 {
-                state.drum_patterns[inst][step] = !state.drum_patterns[inst][step];
-                let _ = audio_cmd_tx.try_send(AudioCmd::ToggleStep(inst, step));
+            let index = state.selected_pattern;
+            let step = state.step_page * AppState::STEP_PAGE_LEN + step;
+            if let Some(lane) = state.pattern_bank.patterns[index].lanes.get_mut(inst) {
+                lane.toggle(step);
+                state.selected_step = step;
+                send_drum_step(state, index, inst, step, audio_cmd_tx);
+            }
+        }
+        UiEvent::AdjustSwing(delta) => {
+            state.swing = (state.swing + delta).clamp(groove::MIN_SWING, groove::MAX_SWING);
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetSwing(state.swing));
+        }
+        UiEvent::AdjustHumanize(delta) => {
+            state.humanize = (state.humanize + delta).clamp(0.0, 1.0);
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetHumanize(state.humanize));
+        }
+        UiEvent::NudgeStep(delta) => {
+            edit_step(state, audio_cmd_tx, |s| s.set_nudge(s.nudge + delta));
+        }
+        UiEvent::SelectStep(step) => {
+            state.selected_step = state.step_page * AppState::STEP_PAGE_LEN + step;
+        }
+        UiEvent::AdjustStepVelocity(delta) => {
+            edit_step(state, audio_cmd_tx, |s| s.set_velocity(s.velocity + delta));
+        }
+        UiEvent::AdjustStepProbability(delta) => {
+            edit_step(state, audio_cmd_tx, |s| s.set_probability(s.probability + delta));
+        }
+        UiEvent::CycleRatchets => {
+            edit_step(state, audio_cmd_tx, |s| s.cycle_ratchets());
+        }
+        UiEvent::ToggleDrumMute => {
+            let inst = state.selected_instrument;
+            state.drum_mutes[inst] = !state.drum_mutes[inst];
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumMute(inst, state.drum_mutes[inst]));
+        }
+        UiEvent::TogglePerformMode => {
+            state.drum_perform = !state.drum_perform;
+        }
+        UiEvent::PlayDrum(inst) => {
+            state.selected_instrument = inst;
+            let _ = audio_cmd_tx.try_send(AudioCmd::TriggerDrum(inst));
+        }
+        UiEvent::CycleQuantize => {
+            // 100% → 75% → 50% → 25% → off
+            state.quantize = if state.quantize <= 0.0 { 1.0 } else { (state.quantize - 0.25).max(0.0) };
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetQuantize(state.quantize));
+        }
+        UiEvent::ToggleLockMode => {
+            state.lock_mode = !state.lock_mode;
+        }
+        UiEvent::ClearLocks => {
+            edit_step(state, audio_cmd_tx, |s| s.locks = Default::default());
+        }
+        UiEvent::AdjustPatternLength(delta) => {
+            let index = state.selected_pattern;
+            let pattern = &mut state.pattern_bank.patterns[index];
+            pattern.set_length(pattern.length.saturating_add_signed(delta as isize));
+            state.step_page = state.step_page.min(state.step_pages() - 1);
+            send_drum_pattern(state, index, audio_cmd_tx);
+        }
+        UiEvent::AdjustLaneLength(delta) => {
+            let index = state.selected_pattern;
+            let lane = state.selected_instrument;
+            let pattern = &mut state.pattern_bank.patterns[index];
+            let length = pattern.lane_length(lane).saturating_add_signed(delta as isize);
+            pattern.set_lane_length(lane, length);
+            state.step_page = state.step_page.min(state.step_pages() - 1);
+            send_drum_pattern(state, index, audio_cmd_tx);
+        }
+        UiEvent::CycleResolution => {
+            let index = state.selected_pattern;
+            let pattern = &mut state.pattern_bank.patterns[index];
+            pattern.resolution = pattern.resolution.next();
+            send_drum_pattern(state, index, audio_cmd_tx);
+        }
+        UiEvent::CycleTimeSignature => {
+            state.time_signature = state.time_signature.next();
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetTimeSignature(state.time_signature));
+        }
+        UiEvent::SelectStepPage(dir) => {
+            let pages = state.step_pages();
+            state.step_page = if dir == 0 {
+                (state.step_page + pages - 1) % pages
+            } else {
+                (state.step_page + 1) % pages
+            };
+        }
+        UiEvent::SelectPattern(dir) => {
+            let count = sequencer::pattern::PATTERN_COUNT;
+            state.selected_pattern = if dir == 0 {
+                (state.selected_pattern + count - 1) % count
+            } else {
+                (state.selected_pattern + 1) % count
+            };
+            state.step_page = state.step_page.min(state.step_pages() - 1);
+            // The edited pattern takes over from the next bar
+            if !state.song_mode {
+                state.queued_pattern =
+                    (state.selected_pattern != state.playing_pattern).then_some(state.selected_pattern);
+                let _ = audio_cmd_tx.try_send(AudioCmd::QueuePattern(state.selected_pattern));
+            }
+        }
+        UiEvent::CopyPattern => {
+            state.pattern_clipboard = Some(state.drum_pattern().clone());
+        }
+        UiEvent::PastePattern => {
+            if let Some(pattern) = state.pattern_clipboard.clone() {
+                let index = state.selected_pattern;
+                state.pattern_bank.patterns[index] = pattern;
+                send_drum_pattern(state, index, audio_cmd_tx);
+            }
+        }
+        UiEvent::ClearPattern => {
+            let index = state.selected_pattern;
+            state.pattern_bank.patterns[index] = sequencer::pattern::DrumPattern::new();
+            send_drum_pattern(state, index, audio_cmd_tx);
+        }
+        UiEvent::ToggleSongMode => {
+            state.song_mode = !state.song_mode;
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetSongMode(state.song_mode));
+            if !state.song_mode {
+                // Carry on with the pattern being edited
+                let _ = audio_cmd_tx.try_send(AudioCmd::QueuePattern(state.selected_pattern));
+            }
+        }
+        UiEvent::AppendSong => {
+            if state.song.len() < MAX_SONG_LENGTH {
+                state.song.push(state.selected_pattern);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetSong(state.song.clone()));
+            }
+        }
+        UiEvent::RemoveSong => {
+            if state.song.pop().is_some() {
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetSong(state.song.clone()));
             }
         }
         UiEvent::SetBpm(delta) => {
@@ -325,6 +704,75 @@ fn handle_ui_event(
                 state.selected_instrument = inst;
             }
         }
+        UiEvent::SelectDrumParam(dir) => {
+            let count = sequencer::drum_kit::DRUM_PARAM_COUNT;
+            state.selected_drum_param = if dir == 0 {
+                (state.selected_drum_param + count - 1) % count
+            } else {
+                (state.selected_drum_param + 1) % count
+            };
+        }
+        UiEvent::AdjustDrumParam(up) if state.lock_mode => {
+            // Lock the parameter on the selected step, starting from the lane's own value
+            let (inst, index) = (state.selected_instrument, state.selected_drum_param);
+            let base = state.drum_params[inst][index];
+            edit_step(state, audio_cmd_tx, |s| {
+                let value = s.locks[index].unwrap_or(base);
+                s.locks[index] = Some(sequencer::drum_kit::step_param(index, value, up));
+            });
+        }
+        UiEvent::AdjustDrumParam(up) => {
+            let (inst, index) = (state.selected_instrument, state.selected_drum_param);
+            if let Some(params) = state.drum_params.get_mut(inst) {
+                params[index] = sequencer::drum_kit::step_param(index, params[index], up);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumParam(inst, index, params[index]));
+            }
+        }
+        UiEvent::CycleDrumKit => {
+            let dirs = sequencer::sample_kit::kit_dirs(std::path::Path::new(KITS_DIR));
+            let first = match &state.drum_kit_path {
+                Some(path) => dirs.iter().position(|d| d == path).map_or(0, |i| i + 1),
+                None => 0,
+            };
+            // Skip kits that fail to load; past the last one, go back to the synthesized voices
+            let mut loaded = false;
+            for dir in dirs.iter().skip(first) {
+                match set_drum_kit(state, Some(dir.clone()), audio_cmd_tx) {
+                    Ok(()) => {
+                        loaded = true;
+                        break;
+                    }
+                    Err(e) => eprintln!("Kit load error: {}", e),
+                }
+            }
+            if !loaded {
+                let _ = set_drum_kit(state, None, audio_cmd_tx);
+            }
+        }
+        UiEvent::SelectMasterSlot(dir) => {
+            let len = state.master_chain.len();
+            if len > 0 {
+                state.selected_master_slot = if dir == 0 {
+                    (state.selected_master_slot + len - 1) % len
+                } else {
+                    (state.selected_master_slot + 1) % len
+                };
+            }
+        }
+        UiEvent::MoveMasterSlot(dir) => {
+            let slot = state.selected_master_slot;
+            let target = if dir == 0 { slot.checked_sub(1) } else { Some(slot + 1) };
+            if let Some(target) = target.filter(|&t| t < state.master_chain.len()) {
+                // The engine reports the new order back; keep the selection on the moved slot
+                state.selected_master_slot = target;
+                let _ = audio_cmd_tx.try_send(AudioCmd::MoveMasterEffect(slot, dir));
+            }
+        }
+        UiEvent::ToggleMasterSlot => {
+            if state.selected_master_slot < state.master_chain.len() {
+                let _ = audio_cmd_tx.try_send(AudioCmd::ToggleMasterEffect(state.selected_master_slot));
+            }
+        }
         UiEvent::ToggleTapeSim => {
             state.tape_sim_enabled = !state.tape_sim_enabled;
             let _ = audio_cmd_tx.try_send(AudioCmd::ToggleTapeSim);
@@ -333,12 +781,52 @@ fn handle_ui_event(
             state.tape_speed = speed;
             let _ = audio_cmd_tx.try_send(AudioCmd::SetTapeSpeed(speed));
         }
-        UiEvent::ToggleEffect(track, slot) => {
-            if track < TRACK_COUNT && slot < 3 {
+        UiEvent::SelectFxSlot => {
+            state.selected_fx_slot = (state.selected_fx_slot + 1) % FX_SLOTS;
+            state.selected_fx_param = 0;
+        }
+        UiEvent::CycleEffect(dir) => {
+            let (track, slot) = (state.selected_track, state.selected_fx_slot);
+            // Empty, then each effect in the factory
+            let position = state.effect_types[track][slot].map_or(0, |e| e + 1);
+            let count = effects::EFFECT_COUNT + 1;
+            let position = if dir == 0 { (position + count - 1) % count } else { (position + 1) % count };
+            let effect = position.checked_sub(1);
+            state.effect_types[track][slot] = effect;
+            state.effect_params[track][slot] = effect.map_or(vec![], effects::default_params);
+            state.effect_bypassed[track][slot] = false;
+            state.selected_fx_param = 0;
+            let _ = audio_cmd_tx.try_send(AudioCmd::SetEffect(track, slot, effect.and_then(effects::create_effect)));
+        }
+        UiEvent::ToggleEffect => {
+            let (track, slot) = (state.selected_track, state.selected_fx_slot);
+            if state.effect_types[track][slot].is_some() {
                 state.effect_bypassed[track][slot] = !state.effect_bypassed[track][slot];
                 let _ = audio_cmd_tx.try_send(AudioCmd::ToggleEffect(track, slot));
             }
         }
+        UiEvent::SelectFxParam(dir) => {
+            let count = state.effect_params[state.selected_track][state.selected_fx_slot].len();
+            if count > 0 {
+                let param = state.selected_fx_param.min(count - 1);
+                state.selected_fx_param = if dir == 0 { (param + count - 1) % count } else { (param + 1) % count };
+            }
+        }
+        UiEvent::AdjustFxParam(dir) => {
+            let (track, slot) = (state.selected_track, state.selected_fx_slot);
+            let Some(effect) = state.effect_types[track][slot] else {
+                return;
+            };
+            let param = state.selected_fx_param.min(state.effect_params[track][slot].len().saturating_sub(1));
+            if let (Some(value), Some(&range)) =
+                (state.effect_params[track][slot].get_mut(param), state.effect_param_ranges[effect].get(param))
+            {
+                let step = effects::param_step(range);
+                let delta = if dir == 0 { -step } else { step };
+                *value = (*value + delta).clamp(range.0, range.1);
+                let _ = audio_cmd_tx.try_send(AudioCmd::SetEffectParam(track, slot, param, *value));
+            }
+        }
         UiEvent::SetEffectParam(track, slot, param, value) => {
             let _ = audio_cmd_tx.try_send(AudioCmd::SetEffectParam(track, slot, param, value));
         }
@@ -346,6 +834,20 @@ fn handle_ui_event(
             if let Ok(bufs) = buffers.lock() {
                 let mut meta = project::metadata::ProjectMeta::new("tapedeck_project");
                 meta.bpm = state.bpm;
+                meta.drum_kit = state.drum_kit_path.clone();
+                meta.drum_params = state.drum_params.iter().map(|p| p.to_vec()).collect();
+                meta.drum_patterns = state.pattern_bank.patterns.clone();
+                meta.song = state.song.clone();
+                meta.song_mode = state.song_mode;
+                meta.time_signature = state.time_signature;
+                meta.swing = state.swing;
+                meta.drum_mutes = state.drum_mutes.to_vec();
+                meta.humanize = state.humanize;
+                meta.master_chain = state
+                    .master_chain
+                    .iter()
+                    .map(|&(insert, bypassed)| MasterInsertMeta { insert: MASTER_INSERTS[insert].to_string(), bypassed })
+                    .collect();
                 for i in 0..TRACK_COUNT {
                     let td = state.track_displays[i];
                     meta.tracks[i].level = td.level;
@@ -353,10 +855,34 @@ fn handle_ui_event(
                     meta.tracks[i].muted = td.muted;
                     meta.tracks[i].solo = td.solo;
                     meta.tracks[i].armed = td.armed;
+                    meta.tracks[i].sends = td.sends.to_vec();
+                    meta.tracks[i].send_pre = td.send_pre.to_vec();
+                    meta.tracks[i].eq = td.eq.to_vec();
+                    meta.tracks[i].effects = (0..FX_SLOTS)
+                        .map(|slot| {
+                            state.effect_types[i][slot].and_then(|effect| {
+                                let params = &state.effect_params[i][slot];
+                                EffectSnapshot::from_values(effect, params, state.effect_bypassed[i][slot])
+                            })
+                        })
+                        .collect();
+                    meta.tracks[i].patch = Some(state.instruments[i].patch());
+                    meta.tracks[i].notes = state.note_patterns[i].clone();
+                    if state.instruments[i].sample.is_some() {
+                        meta.tracks[i].sample = Some(format!("sample_{}.wav", i + 1));
+                    }
                 }
                 let dir = std::path::Path::new("tapedeck_project");
                 if let Err(e) = project::save::save_project(dir, &meta, &bufs) {
                     eprintln!("Save error: {}", e);
+                    return;
+                }
+                for (inst, track_meta) in state.instruments.iter().zip(&meta.tracks) {
+                    if let (Some(sample), Some(file)) = (&inst.sample, &track_meta.sample) {
+                        if let Err(e) = project::save::write_wav_mono_f32(&dir.join(file), sample) {
+                            eprintln!("Save error: {}", e);
+                        }
+                    }
                 }
             }
         }
@@ -368,6 +894,54 @@ fn handle_ui_event(
                         state.bpm = meta.bpm.clamp(40.0, 300.0);
                         let _ = audio_cmd_tx.try_send(AudioCmd::SetBpm(state.bpm));
 
+                        for (inst, saved) in meta.drum_params.iter().enumerate() {
+                            let Some(params) = state.drum_params.get_mut(inst) else {
+                                break;
+                            };
+                            for (index, value) in saved.iter().take(params.len()).enumerate() {
+                                params[index] = sequencer::drum_kit::clamp_param(index, *value);
+                                let _ = audio_cmd_tx
+                                    .try_send(AudioCmd::SetDrumParam(inst, index, params[index]));
+                            }
+                        }
+
+                        for (inst, muted) in state.drum_mutes.iter_mut().enumerate() {
+                            *muted = meta.drum_mutes.get(inst).copied().unwrap_or(false);
+                            let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumMute(inst, *muted));
+                        }
+
+                        // Every slot is replaced, so patterns from the last session don't linger
+                        let count = sequencer::pattern::PATTERN_COUNT;
+                        for index in 0..count {
+                            let mut pattern = meta.drum_patterns.get(index).cloned().unwrap_or_else(DrumPattern::new);
+                            pattern.normalize();
+                            state.pattern_bank.patterns[index] = pattern;
+                            send_drum_pattern(state, index, audio_cmd_tx);
+                        }
+                        state.selected_pattern = 0;
+                        state.playing_pattern = 0;
+                        state.queued_pattern = None;
+                        let _ = audio_cmd_tx.try_send(AudioCmd::ResetPattern);
+                        state.song = meta.song.iter().copied().filter(|p| *p < count).collect();
+                        state.song.truncate(MAX_SONG_LENGTH);
+                        state.song_mode = meta.song_mode;
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetSong(state.song.clone()));
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetSongMode(state.song_mode));
+                        state.step_page = 0;
+                        state.time_signature = meta.time_signature;
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetTimeSignature(state.time_signature));
+                        state.selected_step = 0;
+                        state.swing = meta.swing.clamp(groove::MIN_SWING, groove::MAX_SWING);
+                        state.humanize = meta.humanize.clamp(0.0, 1.0);
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetSwing(state.swing));
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetHumanize(state.humanize));
+                        let _ = audio_cmd_tx.try_send(AudioCmd::SetMasterChain(meta.master_chain()));
+
+                        if let Err(e) = set_drum_kit(state, meta.drum_kit.clone(), audio_cmd_tx) {
+                            eprintln!("Kit load error: {}", e);
+                            let _ = set_drum_kit(state, None, audio_cmd_tx);
+                        }
+
                         let mut armed_assigned = false;
                         for i in 0..TRACK_COUNT {
                             if let Some(track_meta) = meta.tracks.get(i) {
@@ -385,17 +959,57 @@ fn handle_ui_event(
                                 state.track_displays[i].muted = muted;
                                 state.track_displays[i].solo = solo;
                                 state.track_displays[i].armed = armed;
+                                for aux in 0..AUX_COUNT {
+                                    state.track_displays[i].sends[aux] =
+                                        track_meta.sends.get(aux).map_or(0.0, |s| s.clamp(0.0, 1.0));
+                                    state.track_displays[i].send_pre[aux] =
+                                        track_meta.send_pre.get(aux).copied().unwrap_or(false);
+                                }
+                                for band in 0..CHANNEL_EQ_BANDS {
+                                    state.track_displays[i].eq[band] = track_meta
+                                        .eq
+                                        .get(band)
+                                        .map_or(0.0, |g| g.clamp(-CHANNEL_EQ_RANGE, CHANNEL_EQ_RANGE));
+                                }
 
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetLevel(i, level));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetPan(i, pan));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetMute(i, muted));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetSolo(i, solo));
+                                send_strip(state, i, audio_cmd_tx);
+                                load_effects(state, i, &track_meta.effects, audio_cmd_tx);
+
+                                let inst = &mut state.instruments[i];
+                                inst.sample = None;
+                                inst.sample_name.clear();
+                                if let Some(file) = &track_meta.sample {
+                                    match synth::engines::sampler::load_sample_file(&dir.join(file)) {
+                                        Ok(samples) => {
+                                            inst.sample = Some(samples.into());
+                                            inst.sample_name = file.clone();
+                                        }
+                                        Err(e) => eprintln!("Load error: {}", e),
+                                    }
+                                }
+
+                                match &track_meta.patch {
+                                    Some(patch) => apply_patch(state, i, patch, audio_cmd_tx),
+                                    None => send_instrument(state, i, audio_cmd_tx),
+                                }
+
+                                let mut notes = track_meta.notes.clone();
+                                notes.normalize();
+                                state.note_patterns[i] = notes;
+                                state.cleared_notes[i] = None;
+                                send_note_pattern(state, i, audio_cmd_tx);
                             } else {
                                 state.track_displays[i] = TrackDisplay::default();
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetLevel(i, state.track_displays[i].level));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetPan(i, state.track_displays[i].pan));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetMute(i, state.track_displays[i].muted));
                                 let _ = audio_cmd_tx.try_send(AudioCmd::SetSolo(i, state.track_displays[i].solo));
+                                send_strip(state, i, audio_cmd_tx);
+                                load_effects(state, i, &[], audio_cmd_tx);
                             }
                         }
                     }
@@ -405,3 +1019,143 @@ fn handle_ui_event(
         }
     }
 }
+
+/// Push a track's aux sends and channel EQ to the audio thread
+fn send_strip(state: &AppState, track: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let td = &state.track_displays[track];
+    for aux in 0..AUX_COUNT {
+        let _ = audio_cmd_tx.try_send(AudioCmd::SetSend(track, aux, td.sends[aux]));
+        let _ = audio_cmd_tx.try_send(AudioCmd::SetSendPre(track, aux, td.send_pre[aux]));
+    }
+    for (band, gain) in td.eq.iter().enumerate() {
+        let _ = audio_cmd_tx.try_send(AudioCmd::SetChannelEq(track, band, *gain));
+    }
+}
+
+/// Recreate a track's insert slots from saved snapshots; slots without one
+/// (or naming an unknown effect) are left empty
+fn load_effects(
+    state: &mut AppState,
+    track: usize,
+    snapshots: &[Option<EffectSnapshot>],
+    audio_cmd_tx: &Sender<AudioCmd>,
+) {
+    for slot in 0..FX_SLOTS {
+        let snapshot = snapshots.get(slot).and_then(|s| s.as_ref());
+        let effect = snapshot.and_then(|s| Some((s.effect_index()?, s.restore()?)));
+        state.effect_types[track][slot] = effect.as_ref().map(|(index, _)| *index);
+        state.effect_params[track][slot] = effect.as_ref().map_or(vec![], |(_, fx)| effects::param_values(fx.as_ref()));
+        state.effect_bypassed[track][slot] = effect.as_ref().is_some_and(|(_, fx)| fx.is_bypassed());
+
+        let _ = audio_cmd_tx.try_send(AudioCmd::SetEffect(track, slot, effect.map(|(_, fx)| fx)));
+    }
+}
+
+/// Load a drum sample kit (or go back to synthesized drums with `None`)
+fn set_drum_kit(
+    state: &mut AppState,
+    path: Option<std::path::PathBuf>,
+    audio_cmd_tx: &Sender<AudioCmd>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let kit = path
+        .as_deref()
+        .map(sequencer::sample_kit::load_kit)
+        .transpose()?;
+    state.drum_kit_name = kit.as_ref().map_or("SYNTH".to_string(), |k| k.name.clone());
+    state.drum_kit_path = path;
+    let pads = sequencer::drum_kit::DrumKit::lane_pads(kit);
+    state.drum_pads = pads.iter().map(|p| p.as_ref().map(|p| p.config.clone())).collect();
+    let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumKit(pads));
+    Ok(())
+}
+
+/// Edit the selected step of the selected drum lane
+fn edit_step(state: &mut AppState, audio_cmd_tx: &Sender<AudioCmd>, edit: impl FnOnce(&mut Step)) {
+    let index = state.selected_pattern;
+    let lane = state.pattern_bank.patterns[index].lanes.get_mut(state.selected_instrument);
+    if let Some(step) = lane.and_then(|l| l.step_mut(state.selected_step)) {
+        edit(step);
+        send_drum_step(state, index, state.selected_instrument, state.selected_step, audio_cmd_tx);
+    }
+}
+
+fn send_drum_pattern(state: &AppState, index: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let pattern = state.pattern_bank.patterns[index].clone();
+    let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumPattern(index, pattern));
+}
+
+/// Push one edited step, rather than the whole pattern, to the audio thread
+fn send_drum_step(state: &AppState, index: usize, lane: usize, step: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let settings = state.pattern_bank.patterns[index].lanes.get(lane).and_then(|l| l.step(step));
+    if let Some(settings) = settings {
+        let _ = audio_cmd_tx.try_send(AudioCmd::SetDrumStep(index, lane, step, *settings));
+    }
+}
+
+/// Push a track's note pattern to the audio thread
+fn send_note_pattern(state: &AppState, track: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let pattern = state.note_patterns[track].clone();
+    let _ = audio_cmd_tx.try_send(AudioCmd::SetNotePattern(track, pattern));
+}
+
+fn send_arp(state: &AppState, track: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let _ = audio_cmd_tx.try_send(AudioCmd::SetArp(track, state.instruments[track].arp));
+}
+
+/// Switch a track's instrument to a patch's engine and send all of its parameter values
+fn apply_patch(
+    state: &mut AppState,
+    track: usize,
+    patch: &Patch,
+    audio_cmd_tx: &Sender<AudioCmd>,
+) {
+    let Some(engine) = patch.engine_index() else {
+        return;
+    };
+    let values = patch.values(&synth::engines::default_params(engine));
+
+    if track == state.selected_track {
+        state.selected_param = state.selected_param.min(values.len().saturating_sub(1));
+    }
+    let inst = &mut state.instruments[track];
+    inst.engine = engine;
+    inst.params[engine] = values;
+    inst.patch_name = patch.name.clone();
+    send_instrument(state, track, audio_cmd_tx);
+}
+
+/// Start a fresh instance of a track's engine with its remembered settings and
+/// sample. The engine is built here so the audio thread never allocates it.
+fn send_instrument(state: &AppState, track: usize, audio_cmd_tx: &Sender<AudioCmd>) {
+    let inst = &state.instruments[track];
+    let mut engine = synth::engines::create_engine(inst.engine);
+    for (index, value) in inst.engine_params().iter().enumerate() {
+        engine.set_param(index, *value);
+    }
+    if let Some(sample) = &inst.sample {
+        engine.load_sample(Arc::clone(sample));
+    }
+    let _ = audio_cmd_tx.try_send(AudioCmd::SelectEngine(track, engine));
+}
+
+/// Give a track's instrument a new sample, switching it to the sampler engine
+fn load_sample(
+    state: &mut AppState,
+    track: usize,
+    samples: Vec<f32>,
+    name: String,
+    audio_cmd_tx: &Sender<AudioCmd>,
+) {
+    let inst = &mut state.instruments[track];
+    inst.sample = Some(samples.into());
+    inst.sample_name = name;
+    if inst.engine != synth::engines::SAMPLER_ENGINE {
+        inst.engine = synth::engines::SAMPLER_ENGINE;
+        inst.patch_name = "INIT".to_string();
+    }
+    if track == state.selected_track {
+        let count = state.instruments[track].engine_params().len();
+        state.selected_param = state.selected_param.min(count.saturating_sub(1));
+    }
+    send_instrument(state, track, audio_cmd_tx);
+}
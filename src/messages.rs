@@ -1,3 +1,16 @@
+use crate::constants::{AUX_COUNT, MASTER_INSERT_COUNT};
+use crate::effects::eq::CHANNEL_EQ_BANDS;
+use crate::effects::Effect;
+
+use crate::sequencer::clock::TimeSignature;
+use crate::sequencer::drum_kit::LANE_NAMES;
+use crate::sequencer::note_pattern::NotePattern;
+use crate::sequencer::pattern::{DrumPattern, Step};
+use crate::sequencer::sample_kit::SamplePad;
+use crate::synth::arpeggiator::ArpSettings;
+use crate::synth::engines::ParamValues;
+use crate::synth::SynthEngine;
+
 /// Recording source selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RecordSource {
@@ -6,6 +19,8 @@ pub enum RecordSource {
     Internal,
     Synth,
     Drum,
+    /// One drum instrument on its own
+    DrumLane(usize),
     All,
 }
 
@@ -14,7 +29,9 @@ impl RecordSource {
         match self {
             RecordSource::Internal => RecordSource::Synth,
             RecordSource::Synth => RecordSource::Drum,
-            RecordSource::Drum => RecordSource::Mic,
+            RecordSource::Drum => RecordSource::DrumLane(0),
+            RecordSource::DrumLane(lane) if lane + 1 < LANE_NAMES.len() => RecordSource::DrumLane(lane + 1),
+            RecordSource::DrumLane(_) => RecordSource::Mic,
             RecordSource::Mic => RecordSource::All,
             RecordSource::All => RecordSource::Internal,
         }
@@ -26,6 +43,7 @@ impl RecordSource {
             RecordSource::Internal => "INT",
             RecordSource::Synth => "SYNTH",
             RecordSource::Drum => "DRUM",
+            RecordSource::DrumLane(lane) => LANE_NAMES.get(lane).copied().unwrap_or("DRUM"),
             RecordSource::All => "ALL",
         }
     }
@@ -47,35 +65,134 @@ pub enum UiEvent {
     CycleMode,
     SetLevel(usize, f32),
     SetPan(usize, f32),
+    /// Select the aux send to edit (0 = prev, 1 = next)
+    SelectSend(usize),
+    /// Adjust the selected aux send of a track by a delta
+    AdjustSend(usize, f32),
+    /// Toggle the selected aux send of a track between pre and post fader
+    ToggleSendPre(usize),
+    /// Select the channel EQ band to edit (cycles LO → MID → HI)
+    CycleEqBand,
+    /// Adjust the selected channel EQ band of a track by a delta in dB
+    AdjustEq(usize, f32),
     /// Synth note on: (note_number, velocity)
     NoteOn(u8, f32),
     /// Synth note off
     NoteOff(u8),
     /// Synth engine selection
     SelectEngine(usize),
-    /// Parameter change (param_index, value)
-    SetParam(usize, f32),
+    /// Select synth parameter (0 = prev, 1 = next)
+    SelectParam(usize),
+    /// Adjust the selected synth parameter by a delta
+    AdjustParam(f32),
+    /// Browse synth presets (0 = prev, 1 = next), loading the new one
+    BrowsePreset(usize),
+    /// Save the current synth settings as a user preset
+    SavePreset,
+    /// Arpeggiator controls for the selected track's instrument
+    ToggleArp,
+    CycleArpMode,
+    CycleArpOctaves,
+    CycleArpRate,
+    AdjustArpGate(f32),
+    ToggleArpLatch,
+    /// Sample the source track from the playhead into the selected track's sampler
+    SampleTrack,
+    /// Cycle which track `SampleTrack` copies from
+    CycleSampleSource,
+    /// Load the next WAV from the samples directory into the selected track's sampler
+    LoadSampleFile,
+    /// Note sequencer: move the cursor by (steps, semitones)
+    MoveNoteCursor(i32, i32),
+    /// Note sequencer: add or remove a note at the cursor
+    ToggleNote,
+    /// Note sequencer: change the length of the note at the cursor (steps)
+    AdjustNoteLength(i32),
+    /// Note sequencer: change the velocity of the note at the cursor
+    AdjustNoteVelocity(f32),
+    AddNoteBar,
+    RemoveNoteBar,
+    ClearNotes,
+    /// Swap the selected track's pattern with the one it had before the last clear
+    UndoClearNotes,
     /// Drum sequencer: toggle step (instrument, step)
     ToggleStep(usize, usize),
     /// Set BPM
     SetBpm(f32),
     /// Select drum instrument
     SelectInstrument(usize),
+    /// Select drum voice parameter (0 = prev, 1 = next)
+    SelectDrumParam(usize),
+    /// Step the selected drum voice parameter up (true) or down
+    AdjustDrumParam(bool),
+    /// Select drum pattern to edit and play next (0 = prev, 1 = next)
+    SelectPattern(usize),
+    CopyPattern,
+    PastePattern,
+    ClearPattern,
+    ToggleSongMode,
+    /// Append the selected pattern to the song chain
+    AppendSong,
+    /// Remove the last pattern from the song chain
+    RemoveSong,
+    /// Change the selected drum pattern's length in steps
+    AdjustPatternLength(i32),
+    /// Change the selected lane's length in steps (polymeter)
+    AdjustLaneLength(i32),
+    CycleResolution,
+    CycleTimeSignature,
+    /// Show the previous (0) or next (1) 16 steps of the drum pattern
+    SelectStepPage(usize),
+    AdjustSwing(f32),
+    AdjustHumanize(f32),
+    /// Move the selected drum step off the grid by a fraction of a step
+    NudgeStep(f32),
+    /// Select a drum step (on the shown page) without toggling it
+    SelectStep(usize),
+    AdjustStepVelocity(f32),
+    AdjustStepProbability(f32),
+    CycleRatchets,
+    /// Mute or unmute the selected drum instrument
+    ToggleDrumMute,
+    /// Switch the drum keys between editing steps and playing instruments
+    TogglePerformMode,
+    /// Play a drum instrument from the keyboard
+    PlayDrum(usize),
+    CycleQuantize,
+    /// Send drum parameter edits to the selected step's locks instead of the voice
+    ToggleLockMode,
+    ClearLocks,
+    /// Switch to the next drum kit (synthesized, then each sample kit)
+    CycleDrumKit,
     /// Toggle tape simulation
     ToggleTapeSim,
     /// Set tape speed (0.5, 1.0, 2.0)
     SetTapeSpeed(f32),
-    /// Toggle effect bypass (track, slot)
-    ToggleEffect(usize, usize),
+    /// Select the selected track's insert slot to edit (cycles 1 → 2 → 3)
+    SelectFxSlot,
+    /// Change the effect in the selected insert slot (0 = prev, 1 = next), passing through empty
+    CycleEffect(usize),
+    /// Toggle bypass of the selected insert slot
+    ToggleEffect,
+    /// Select a parameter of the selected insert slot (0 = prev, 1 = next)
+    SelectFxParam(usize),
+    /// Step the selected insert parameter down (0) or up (1)
+    AdjustFxParam(usize),
     /// Set effect parameter (track, slot, param, value)
     SetEffectParam(usize, usize, usize, f32),
+    /// Select a master bus slot (0 = prev, 1 = next)
+    SelectMasterSlot(usize),
+    /// Move the selected master slot (0 = earlier, 1 = later)
+    MoveMasterSlot(usize),
+    /// Toggle bypass of the selected master slot
+    ToggleMasterSlot,
     SaveProject,
     LoadProject(String),
     Quit,
 }
 
 /// Messages from Control thread → Audio thread
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum AudioCmd {
     Play,
     Pause,
@@ -88,27 +205,108 @@ pub enum AudioCmd {
     SetPan(usize, f32),
     SetMute(usize, bool),
     SetSolo(usize, bool),
-    NoteOn(u8, f32),
-    NoteOff(u8),
-    SelectEngine(usize),
-    SetParam(usize, f32),
-    ToggleStep(usize, usize),
+    /// Aux send level: (track, aux, level)
+    SetSend(usize, usize, f32),
+    /// Aux send tap point: (track, aux, pre_fader)
+    SetSendPre(usize, usize, bool),
+    /// Channel EQ band gain: (track, band, gain_db)
+    SetChannelEq(usize, usize, f32),
+    /// Synth note on: (track, note_number, velocity)
+    NoteOn(usize, u8, f32),
+    /// Synth note off: (track, note_number)
+    NoteOff(usize, u8),
+    /// Synth engine for a track: (track, engine)
+    /// Replace a track's synth engine with one built on the UI thread: (track, engine)
+    SelectEngine(usize, Box<dyn SynthEngine>),
+    /// Synth parameter for a track: (track, param_index, value)
+    SetParam(usize, usize, f32),
+    /// Arpeggiator settings for a track: (track, settings)
+    SetArp(usize, ArpSettings),
+    /// Replace a drum pattern in the bank: (pattern, contents)
+    SetDrumPattern(usize, DrumPattern),
+    /// Replace one step of a drum pattern: (pattern, lane, step, settings)
+    SetDrumStep(usize, usize, usize, Step),
+    /// Play pattern A straight away and drop any queued switch, as after loading a project
+    ResetPattern,
+    /// Switch to a drum pattern at the start of the next bar
+    QueuePattern(usize),
+    /// Song chain of pattern indices, each entry playing its pattern through once
+    SetSong(Vec<usize>),
+    /// Play the song chain instead of the queued pattern
+    SetSongMode(bool),
+    /// Bar length for pattern switching, the song chain and the count-in
+    SetTimeSignature(TimeSignature),
+    /// Drum swing, 0.5 (straight) to 0.75
+    SetSwing(f32),
+    /// Silence a drum instrument: (instrument, muted)
+    SetDrumMute(usize, bool),
+    /// Play a drum instrument now, writing it into the pattern while the tape runs
+    TriggerDrum(usize),
+    /// Quantize strength for recorded drum hits, 0-1
+    SetQuantize(f32),
+    /// Drum timing and velocity jitter, 0-1
+    SetHumanize(f32),
+    /// Drum voice parameter: (instrument, param_index, value in the param's unit)
+    SetDrumParam(usize, usize, f32),
+    /// Sample pads for the drum lanes from `DrumKit::lane_pads` (`None` = synthesized voice)
+    SetDrumKit(Vec<Option<SamplePad>>),
+    /// Replace a track's note sequencer pattern: (track, pattern)
+    SetNotePattern(usize, NotePattern),
     SetBpm(f32),
     ToggleTapeSim,
     SetTapeSpeed(f32),
+    /// Put an effect built on the UI thread in a track's insert slot, or empty it: (track, slot, effect)
+    SetEffect(usize, usize, Option<Box<dyn Effect>>),
     ToggleEffect(usize, usize),
     SetEffectParam(usize, usize, usize, f32),
+    /// Move a master insert: (slot, 0 = earlier / 1 = later)
+    MoveMasterEffect(usize, usize),
+    ToggleMasterEffect(usize),
+    /// Reorder the master inserts and set their bypass, e.g. from a loaded project:
+    /// (index into `MASTER_INSERTS`, bypassed) in processing order
+    SetMasterChain([(usize, bool); MASTER_INSERT_COUNT]),
     SetRecordSource(RecordSource),
 }
 
 /// Messages from Audio thread → Control thread
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum AudioMsg {
     Position(usize),
+    /// Sequencer steps elapsed since the clock started
     CurrentStep(usize),
+    /// Drum pattern now playing
+    CurrentPattern(usize),
+    /// Song chain entry now playing
+    SongEntry(usize),
+    /// Steps into the playing drum pattern, at its resolution
+    DrumStep(usize),
+    /// A live hit was written into a pattern: (pattern, instrument, step, nudge)
+    StepRecorded(usize, usize, usize, f32),
     Levels([f32; 4]),
     Peaks([f32; 4]),
     MasterLevel(f32, f32),
+    /// Peak limiter gain reduction in dB since the last report
+    MasterGainReduction(f32),
+    /// Gain reduction in dB of each master insert since the last report
+    InsertGainReduction([f32; MASTER_INSERT_COUNT]),
+    /// Master inserts (index into `MASTER_INSERTS`) and bypass states, in processing order
+    MasterChain([(usize, bool); MASTER_INSERT_COUNT]),
+    /// Actual synth parameter values after a change (track, engine, values)
+    SynthParams(usize, usize, ParamValues),
+    /// Something the audio thread swapped out, to be freed on this side
+    Retired(Retired),
+}
+
+/// Data the audio thread is done with. Sent back rather than dropped in the
+/// callback, where freeing memory could block.
+#[derive(Debug)]
+pub enum Retired {
+    DrumPads(Vec<Option<SamplePad>>),
+    DrumPattern(DrumPattern),
+    NotePattern(NotePattern),
+    Song(Vec<usize>),
+    Effect(Box<dyn Effect>),
+    Engine(Box<dyn SynthEngine>),
 }
 
 /// Messages from Control thread → UI thread
@@ -138,6 +336,10 @@ pub struct TrackDisplay {
     pub solo: bool,
     pub level: f32,
     pub pan: f32,
+    pub sends: [f32; AUX_COUNT],
+    pub send_pre: [bool; AUX_COUNT],
+    /// Channel EQ gains in dB: low, mid, high
+    pub eq: [f32; CHANNEL_EQ_BANDS],
 }
 
 impl Default for TrackDisplay {
@@ -148,6 +350,9 @@ impl Default for TrackDisplay {
             solo: false,
             level: 0.8,
             pan: 0.0,
+            sends: [0.0; AUX_COUNT],
+            send_pre: [false; AUX_COUNT],
+            eq: [0.0; CHANNEL_EQ_BANDS],
         }
     }
 }
@@ -26,7 +26,7 @@ pub fn load_project(
             continue;
         }
 
-        let samples = read_wav_mono_f32(&path)?;
+        let (samples, _) = read_wav_mono_f32(&path)?;
 
         let len = samples.len().min(buffers.tracks[i].data.len());
         buffers.tracks[i].data[..len].copy_from_slice(&samples[..len]);
@@ -36,7 +36,8 @@ pub fn load_project(
     Ok(meta)
 }
 
-fn read_wav_mono_f32(path: &Path) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+/// Read a WAV downmixed to mono, with its sample rate
+pub(crate) fn read_wav_mono_f32(path: &Path) -> Result<(Vec<f32>, u32), Box<dyn std::error::Error>> {
     let mut reader = WavReader::open(path)?;
     let spec = reader.spec();
 
@@ -73,7 +74,7 @@ fn read_wav_mono_f32(path: &Path) -> Result<Vec<f32>, Box<dyn std::error::Error>
     };
 
     if spec.channels == 1 {
-        return Ok(samples);
+        return Ok((samples, spec.sample_rate));
     }
 
     // Downmix interleaved multichannel audio to mono.
@@ -82,5 +83,5 @@ fn read_wav_mono_f32(path: &Path) -> Result<Vec<f32>, Box<dyn std::error::Error>
     for frame in samples.chunks_exact(ch) {
         mono.push(frame.iter().copied().sum::<f32>() / ch as f32);
     }
-    Ok(mono)
+    Ok((mono, spec.sample_rate))
 }
@@ -1,5 +1,14 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use crate::constants::{MASTER_INSERTS, MASTER_INSERT_COUNT};
+use crate::effects::snapshot::EffectSnapshot;
+use crate::sequencer::clock::TimeSignature;
+use crate::sequencer::note_pattern::NotePattern;
+use crate::sequencer::pattern::DrumPattern;
+use crate::synth::patch::Patch;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProjectMeta {
     pub name: String,
@@ -7,6 +16,41 @@ pub struct ProjectMeta {
     pub track_count: usize,
     pub sample_rate: u32,
     pub tracks: Vec<TrackMeta>,
+    /// Directory of the drum sample kit, if one was loaded
+    #[serde(default)]
+    pub drum_kit: Option<PathBuf>,
+    /// Drum voice parameters: [instrument][param]
+    #[serde(default)]
+    pub drum_params: Vec<Vec<f32>>,
+    #[serde(default)]
+    pub drum_mutes: Vec<bool>,
+    #[serde(default)]
+    pub drum_patterns: Vec<DrumPattern>,
+    /// Song chain of drum pattern indices, each entry playing its pattern through once
+    #[serde(default)]
+    pub song: Vec<usize>,
+    #[serde(default)]
+    pub song_mode: bool,
+    #[serde(default)]
+    pub time_signature: TimeSignature,
+    #[serde(default = "default_swing")]
+    pub swing: f32,
+    #[serde(default)]
+    pub humanize: f32,
+    /// Master bus inserts in processing order
+    #[serde(default)]
+    pub master_chain: Vec<MasterInsertMeta>,
+}
+
+fn default_swing() -> f32 {
+    0.5
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MasterInsertMeta {
+    /// Insert name as listed in `MASTER_INSERTS`
+    pub insert: String,
+    pub bypassed: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +62,27 @@ pub struct TrackMeta {
     pub solo: bool,
     pub armed: bool,
     pub filename: String,
+    /// Instrument patch assigned to this track
+    #[serde(default)]
+    pub patch: Option<Patch>,
+    /// WAV file holding the sampler's sample, if it has one
+    #[serde(default)]
+    pub sample: Option<String>,
+    /// Note sequencer pattern playing this track's instrument
+    #[serde(default)]
+    pub notes: NotePattern,
+    /// Aux send levels, one per bus
+    #[serde(default)]
+    pub sends: Vec<f32>,
+    /// Whether each aux send is pre-fader
+    #[serde(default)]
+    pub send_pre: Vec<bool>,
+    /// Channel EQ gains in dB: low, mid, high
+    #[serde(default)]
+    pub eq: Vec<f32>,
+    /// Effects in the insert slots, in processing order
+    #[serde(default)]
+    pub effects: Vec<Option<EffectSnapshot>>,
 }
 
 impl ProjectMeta {
@@ -36,8 +101,64 @@ impl ProjectMeta {
                     solo: false,
                     armed: false,
                     filename: format!("track_{}.wav", i + 1),
+                    patch: None,
+                    sample: None,
+                    notes: NotePattern::new(),
+                    sends: vec![],
+                    send_pre: vec![],
+                    eq: vec![],
+                    effects: vec![],
                 })
                 .collect(),
+            drum_kit: None,
+            drum_params: vec![],
+            drum_mutes: vec![],
+            drum_patterns: vec![],
+            song: vec![],
+            song_mode: false,
+            time_signature: TimeSignature::default(),
+            swing: 0.5,
+            humanize: 0.0,
+            master_chain: vec![],
         }
     }
+
+    /// Master inserts (index into `MASTER_INSERTS`) and bypass states, in order.
+    /// Projects without a complete chain get the default: every insert in its
+    /// original place and bypassed.
+    pub fn master_chain(&self) -> [(usize, bool); MASTER_INSERT_COUNT] {
+        let default = std::array::from_fn(|insert| (insert, true));
+        if self.master_chain.len() != MASTER_INSERT_COUNT {
+            return default;
+        }
+        let mut chain = default;
+        for (slot, saved) in self.master_chain.iter().enumerate() {
+            let Some(insert) = MASTER_INSERTS.iter().position(|name| *name == saved.insert) else {
+                return default;
+            };
+            if chain[..slot].iter().any(|(placed, _)| *placed == insert) {
+                return default;
+            }
+            chain[slot] = (insert, saved.bypassed);
+        }
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn master_chain_falls_back_to_the_default_order() {
+        let mut meta = ProjectMeta::new("chain");
+        assert_eq!(meta.master_chain(), [(0, true), (1, true), (2, true), (3, true)]);
+
+        let insert = |name: &str, bypassed| MasterInsertMeta { insert: name.to_string(), bypassed };
+        meta.master_chain = vec![insert("TAPE", false), insert("GATE", true), insert("COMP", false), insert("EQ", true)];
+        assert_eq!(meta.master_chain(), [(3, false), (0, true), (2, false), (1, true)]);
+
+        meta.master_chain[1] = insert("TAPE", true);
+        assert_eq!(meta.master_chain(), [(0, true), (1, true), (2, true), (3, true)]);
+    }
 }
@@ -21,17 +21,7 @@ pub fn save_project(
         }
 
         let path = dir.join(&meta.tracks[i].filename);
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-        let mut writer = hound::WavWriter::create(path, spec)?;
-        for j in 0..len {
-            writer.write_sample(track.data[j])?;
-        }
-        writer.finalize()?;
+        write_wav_mono_f32(&path, &track.data[..len])?;
     }
 
     // Save metadata
@@ -41,3 +31,18 @@ pub fn save_project(
 
     Ok(())
 }
+
+pub(crate) fn write_wav_mono_f32(path: &Path, samples: &[f32]) -> Result<(), Box<dyn std::error::Error>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for sample in samples {
+        writer.write_sample(*sample)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
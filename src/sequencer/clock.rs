@@ -1,5 +1,74 @@
+use serde::{Deserialize, Serialize};
+
 use crate::constants::SAMPLE_RATE;
 
+/// Time signatures offered in the drum view, in cycle order
+pub const TIME_SIGNATURES: [TimeSignature; 6] = [
+    TimeSignature { beats: 4, unit: 4 },
+    TimeSignature { beats: 3, unit: 4 },
+    TimeSignature { beats: 5, unit: 4 },
+    TimeSignature { beats: 7, unit: 4 },
+    TimeSignature { beats: 6, unit: 8 },
+    TimeSignature { beats: 7, unit: 8 },
+];
+
+/// Bar length as `beats` notes of value 1/`unit`. BPM always counts quarter notes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSignature {
+    pub beats: u8,
+    pub unit: u8,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TIME_SIGNATURES[0]
+    }
+}
+
+impl TimeSignature {
+    pub fn next(self) -> Self {
+        let index = TIME_SIGNATURES.iter().position(|t| *t == self).unwrap_or(0);
+        TIME_SIGNATURES[(index + 1) % TIME_SIGNATURES.len()]
+    }
+
+    pub fn label(self) -> String {
+        format!("{}/{}", self.beats, self.unit)
+    }
+
+    /// Samples in one beat of the signature's note value
+    pub fn beat_samples(self, bpm: f32) -> f64 {
+        samples_per_quarter(bpm) * 4.0 / self.unit.max(1) as f64
+    }
+
+    pub fn bar_samples(self, bpm: f32) -> f64 {
+        self.beat_samples(bpm) * self.beats.max(1) as f64
+    }
+
+    /// Quarter notes in one bar
+    pub fn quarter_notes(self) -> f64 {
+        self.beats.max(1) as f64 * 4.0 / self.unit.max(1) as f64
+    }
+
+    /// Bar of the timeline at `sample_position`
+    pub fn bar(self, sample_position: usize, bpm: f32) -> usize {
+        (sample_position as f64 / self.bar_samples(bpm).max(1.0)) as usize
+    }
+
+    /// Steps in a bar at `steps_per_quarter` steps per quarter note
+    pub fn steps_per_bar(self, steps_per_quarter: usize) -> usize {
+        (self.beats as usize * steps_per_quarter * 4 / self.unit.max(1) as usize).max(1)
+    }
+
+    /// Steps in one beat of the signature's note value
+    pub fn steps_per_beat(self, steps_per_quarter: usize) -> usize {
+        (steps_per_quarter * 4 / self.unit.max(1) as usize).max(1)
+    }
+}
+
+fn samples_per_quarter(bpm: f32) -> f64 {
+    SAMPLE_RATE as f64 * 60.0 / bpm.clamp(40.0, 300.0) as f64
+}
+
 /// BPM clock that derives step timing from sample position
 pub struct SequencerClock {
     bpm: f32,
@@ -22,12 +91,11 @@ impl SequencerClock {
         self.bpm
     }
 
-    /// Given a sample position, return the current 16th-note step (0-15)
-    /// and whether we just advanced to a new step
-    pub fn tick(&mut self, sample_position: usize) -> (usize, bool) {
-        let samples_per_beat = (SAMPLE_RATE as f32 * 60.0 / self.bpm) as usize;
-        let samples_per_step = samples_per_beat / 4; // 16th notes
-        let step = (sample_position / samples_per_step.max(1)) % 16;
+    /// Given a sample position, return the steps elapsed at `steps_per_beat`
+    /// steps per quarter note and whether we just advanced to a new step
+    pub fn tick(&mut self, sample_position: usize, steps_per_beat: usize) -> (usize, bool) {
+        let samples_per_step = samples_per_quarter(self.bpm) / steps_per_beat.max(1) as f64;
+        let step = (sample_position as f64 / samples_per_step.max(1.0)) as usize;
         let new_step = step != self.last_step;
         self.last_step = step;
         (step, new_step)
@@ -1,14 +1,78 @@
 use crate::constants::SAMPLE_RATE;
+use crate::sequencer::sample_kit::{SampleKit, SamplePad};
+use crate::tape::speed::cubic_interpolate;
+
+/// Envelope multiplier for a choked pad, ~5 ms to -60 dB
+const CHOKE_DECAY: f32 = 0.969;
+
+/// Editable voice parameters, in `DrumVoice::set_param` order
+pub const DRUM_PARAM_COUNT: usize = 6;
+pub const DRUM_PARAM_NAMES: [&str; DRUM_PARAM_COUNT] = ["TUNE", "DECAY", "TONE", "SWEEP", "LEVEL", "PAN"];
+/// Drum lanes, in sequencer order
+pub const LANE_NAMES: [&str; 6] = ["KICK", "SNARE", "HAT", "CLAP", "TOM", "RIM"];
+
+/// Per-sample multiplier that falls to -60 dB over `ms` milliseconds
+fn decay_coefficient(ms: f32) -> f32 {
+    let samples = (ms / 1000.0 * SAMPLE_RATE as f32).max(1.0);
+    0.001f32.powf(1.0 / samples)
+}
+
+/// Clamp a drum parameter to its range
+pub fn clamp_param(index: usize, value: f32) -> f32 {
+    match index {
+        0 => value.clamp(-24.0, 24.0),   // semitones
+        1 => value.clamp(10.0, 5000.0),  // ms
+        2 => value.clamp(0.0, 1.0),      // noise mix
+        3 => value.clamp(0.0, 1000.0),   // Hz
+        4 => value.clamp(0.0, 1.0),      // gain
+        5 => value.clamp(-1.0, 1.0),     // pan
+        _ => value,
+    }
+}
+
+/// Step a drum parameter up or down by one increment of its unit
+pub fn step_param(index: usize, value: f32, up: bool) -> f32 {
+    let sign = if up { 1.0 } else { -1.0 };
+    let next = match index {
+        0 => value + sign,
+        // Decay steps are proportional so short and long decays both feel even
+        1 => if up { value * 1.15 } else { value / 1.15 },
+        3 => value + sign * 10.0,
+        5 => value + sign * 0.1,
+        _ => value + sign * 0.05,
+    };
+    clamp_param(index, next)
+}
+
+/// Format a drum parameter with its unit
+pub fn format_param(index: usize, value: f32) -> String {
+    match index {
+        0 => format!("{:+.0}st", value),
+        1 => format!("{:.0}ms", value),
+        3 => format!("{:.0}Hz", value),
+        5 if value.abs() < 0.05 => "C".to_string(),
+        5 if value < 0.0 => format!("L{:.0}", -value * 100.0),
+        5 => format!("R{:.0}", value * 100.0),
+        _ => format!("{:.2}", value),
+    }
+}
 
 /// Drum instrument synthesized from scratch
 pub struct DrumVoice {
     phase: f64,
+    /// Untuned base frequency
+    base_freq: f64,
     freq: f64,
+    /// Tuning offset in semitones
+    tune: f32,
     envelope: f32,
+    decay_ms: f32,
     decay: f32,
     active: bool,
     noise_state: u32,
     noise_amount: f32,
+    level: f32,
+    pan: f32,
     // Pitch envelope
     pitch_env: f32,
     pitch_decay: f32,
@@ -16,13 +80,71 @@ pub struct DrumVoice {
 }
 
 impl DrumVoice {
-    pub fn trigger(&mut self) {
+    fn new(
+        freq: f64,
+        decay_ms: f32,
+        noise_amount: f32,
+        pitch_amount: f64,
+        pitch_decay_ms: f32,
+        seed: u32,
+    ) -> Self {
+        Self {
+            phase: 0.0,
+            base_freq: freq,
+            freq,
+            tune: 0.0,
+            envelope: 0.0,
+            decay_ms,
+            decay: decay_coefficient(decay_ms),
+            active: false,
+            noise_state: seed,
+            noise_amount,
+            level: 1.0,
+            pan: 0.0,
+            pitch_env: 0.0,
+            pitch_decay: decay_coefficient(pitch_decay_ms),
+            pitch_amount,
+        }
+    }
+
+    pub fn trigger(&mut self, velocity: f32) {
         self.phase = 0.0;
-        self.envelope = 1.0;
+        self.envelope = velocity.clamp(0.0, 1.0);
         self.pitch_env = 1.0;
         self.active = true;
     }
 
+    pub fn set_param(&mut self, index: usize, value: f32) {
+        let value = clamp_param(index, value);
+        match index {
+            0 => {
+                self.tune = value;
+                self.freq = self.base_freq * 2.0f64.powf(value as f64 / 12.0);
+            }
+            1 => {
+                self.decay_ms = value;
+                self.decay = decay_coefficient(value);
+            }
+            2 => self.noise_amount = value,
+            3 => self.pitch_amount = value as f64,
+            4 => self.level = value,
+            5 => self.pan = value,
+            _ => {}
+        }
+    }
+
+    pub fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.tune,
+            1 => self.decay_ms,
+            2 => self.noise_amount,
+            3 => self.pitch_amount as f32,
+            4 => self.level,
+            5 => self.pan,
+            _ => 0.0,
+        }
+    }
+
     pub fn process(&mut self) -> f32 {
         if !self.active {
             return 0.0;
@@ -50,73 +172,319 @@ impl DrumVoice {
             self.active = false;
         }
 
-        sample * self.envelope
+        sample * self.envelope * self.level
     }
 }
 
+/// Playback state of a sample pad
+#[derive(Clone, Copy, Default)]
+struct PadVoice {
+    position: f64,
+    /// Playback rate, from the pad's pitch and the lane's TUNE
+    rate: f64,
+    envelope: f32,
+    decay: f32,
+    active: bool,
+}
+
 pub struct DrumKit {
     pub voices: Vec<DrumVoice>,
     pub names: Vec<&'static str>,
+    /// Sample pads standing in for the synthesized voices, per lane
+    pads: Vec<Option<SamplePad>>,
+    pad_voices: Vec<PadVoice>,
+    /// Voice parameters as set, which parameter locks override for one hit
+    params: Vec<[f32; DRUM_PARAM_COUNT]>,
+    mutes: Vec<bool>,
+    /// Each lane's output from the last `process`, after level and mute
+    outputs: Vec<f32>,
 }
 
 impl DrumKit {
     pub fn new() -> Self {
+        // Decays are the time to fall 60 dB
         let voices = vec![
-            // Kick — long boom, ~300ms decay
-            DrumVoice {
-                phase: 0.0, freq: 55.0, envelope: 0.0, decay: 0.99995,
-                active: false, noise_state: 1, noise_amount: 0.05,
-                pitch_env: 0.0, pitch_decay: 0.999, pitch_amount: 200.0,
-            },
-            // Snare — sharp crack + noise tail, ~200ms
-            DrumVoice {
-                phase: 0.0, freq: 180.0, envelope: 0.0, decay: 0.99992,
-                active: false, noise_state: 2, noise_amount: 0.6,
-                pitch_env: 0.0, pitch_decay: 0.998, pitch_amount: 80.0,
-            },
-            // Hi-hat — bright noise, ~80ms
-            DrumVoice {
-                phase: 0.0, freq: 800.0, envelope: 0.0, decay: 0.99984,
-                active: false, noise_state: 3, noise_amount: 0.95,
-                pitch_env: 0.0, pitch_decay: 0.999, pitch_amount: 0.0,
-            },
-            // Clap — noise burst, ~120ms
-            DrumVoice {
-                phase: 0.0, freq: 400.0, envelope: 0.0, decay: 0.99988,
-                active: false, noise_state: 4, noise_amount: 0.8,
-                pitch_env: 0.0, pitch_decay: 0.997, pitch_amount: 50.0,
-            },
-            // Tom — medium boom, ~250ms
-            DrumVoice {
-                phase: 0.0, freq: 100.0, envelope: 0.0, decay: 0.99994,
-                active: false, noise_state: 5, noise_amount: 0.1,
-                pitch_env: 0.0, pitch_decay: 0.9995, pitch_amount: 150.0,
-            },
-            // Rim — short click, ~60ms
-            DrumVoice {
-                phase: 0.0, freq: 600.0, envelope: 0.0, decay: 0.99980,
-                active: false, noise_state: 6, noise_amount: 0.3,
-                pitch_env: 0.0, pitch_decay: 0.996, pitch_amount: 100.0,
-            },
+            // Kick — long boom
+            DrumVoice::new(55.0, 3133.0, 0.05, 200.0, 157.0, 1),
+            // Snare — sharp crack + noise tail
+            DrumVoice::new(180.0, 1958.0, 0.6, 80.0, 78.0, 2),
+            // Hi-hat — bright noise
+            DrumVoice::new(800.0, 979.0, 0.95, 0.0, 157.0, 3),
+            // Clap — noise burst
+            DrumVoice::new(400.0, 1305.0, 0.8, 50.0, 52.0, 4),
+            // Tom — medium boom
+            DrumVoice::new(100.0, 2611.0, 0.1, 150.0, 313.0, 5),
+            // Rim — short click
+            DrumVoice::new(600.0, 783.0, 0.3, 100.0, 39.0, 6),
         ];
 
+        let lanes = voices.len();
+        let params = voices
+            .iter()
+            .map(|v| std::array::from_fn(|i| v.get_param(i)))
+            .collect();
         Self {
             voices,
-            names: vec!["KICK", "SNARE", "HAT", "CLAP", "TOM", "RIM"],
+            params,
+            names: LANE_NAMES.to_vec(),
+            pads: vec![None; lanes],
+            pad_voices: vec![PadVoice::default(); lanes],
+            mutes: vec![false; lanes],
+            outputs: vec![0.0; lanes],
+        }
+    }
+
+    /// Lay out a kit's pads one per lane for `set_kit`, or all-synthesized voices
+    /// for `None`. Done on the UI thread so the audio thread only swaps them in.
+    pub fn lane_pads(kit: Option<SampleKit>) -> Vec<Option<SamplePad>> {
+        let mut pads = kit.map(|k| k.pads).unwrap_or_default();
+        pads.resize(LANE_NAMES.len(), None);
+        pads
+    }
+
+    /// Play the pads from `lane_pads`, returning the ones they replace. Pads
+    /// that don't match the lanes are handed straight back.
+    pub fn set_kit(&mut self, pads: Vec<Option<SamplePad>>) -> Vec<Option<SamplePad>> {
+        if pads.len() != self.voices.len() {
+            return pads;
+        }
+        self.pad_voices.fill(PadVoice::default());
+        std::mem::replace(&mut self.pads, pads)
+    }
+
+    pub fn set_param(&mut self, instrument: usize, index: usize, value: f32) {
+        if let (Some(voice), Some(params)) = (self.voices.get_mut(instrument), self.params.get_mut(instrument)) {
+            if index < DRUM_PARAM_COUNT {
+                params[index] = clamp_param(index, value);
+                voice.set_param(index, value);
+            }
+        }
+    }
+
+    pub fn set_mute(&mut self, instrument: usize, muted: bool) {
+        if let Some(m) = self.mutes.get_mut(instrument) {
+            *m = muted;
         }
     }
 
+    /// A lane's output from the last `process`, before panning
+    pub fn lane_output(&self, instrument: usize) -> f32 {
+        self.outputs.get(instrument).copied().unwrap_or(0.0)
+    }
+
+    /// A lane's output from the last `process`, panned
+    pub fn lane_stereo(&self, instrument: usize) -> (f32, f32) {
+        let sample = self.lane_output(instrument);
+        let pan = self.voices.get(instrument).map_or(0.0, |v| v.pan);
+        (sample * (1.0 - pan.max(0.0)), sample * (1.0 + pan.min(0.0)))
+    }
+
+    /// Current parameter values of every voice: [instrument][param]
+    pub fn params(&self) -> Vec<[f32; DRUM_PARAM_COUNT]> {
+        self.params.clone()
+    }
+
+    /// Set up a lane's voice for its next hit: locked parameters replace the
+    /// lane's own values, which come back on the next hit without locks
+    pub fn apply_locks(&mut self, instrument: usize, locks: &[Option<f32>; DRUM_PARAM_COUNT]) {
+        let (Some(voice), Some(params)) = (self.voices.get_mut(instrument), self.params.get(instrument)) else {
+            return;
+        };
+        for (index, lock) in locks.iter().enumerate() {
+            let value = lock.unwrap_or(params[index]);
+            if voice.get_param(index) != value {
+                voice.set_param(index, value);
+            }
+        }
+    }
+
+    /// Play a lane now with its own settings, as from a pad
     pub fn trigger(&mut self, instrument: usize) {
-        if instrument < self.voices.len() {
-            self.voices[instrument].trigger();
+        self.apply_locks(instrument, &[None; DRUM_PARAM_COUNT]);
+        self.trigger_velocity(instrument, 1.0);
+    }
+
+    /// Trigger a lane at `velocity` (0-1)
+    pub fn trigger_velocity(&mut self, instrument: usize, velocity: f32) {
+        if instrument >= self.voices.len() {
+            return;
         }
+
+        let Some(pad) = &self.pads[instrument] else {
+            self.voices[instrument].trigger(velocity);
+            return;
+        };
+
+        if let Some(group) = pad.choke {
+            for (other, voice) in self.pad_voices.iter_mut().enumerate() {
+                let same_group = self.pads[other].as_ref().is_some_and(|p| p.choke == Some(group));
+                if other != instrument && same_group && voice.active {
+                    voice.decay = CHOKE_DECAY;
+                }
+            }
+        }
+
+        let voice = &self.voices[instrument];
+        self.pad_voices[instrument] = PadVoice {
+            position: 0.0,
+            rate: pad.rate * voice.freq / voice.base_freq,
+            envelope: velocity.clamp(0.0, 1.0),
+            decay: pad.decay,
+            active: true,
+        };
     }
 
-    pub fn process(&mut self) -> f32 {
-        let mut sum = 0.0f32;
-        for voice in &mut self.voices {
-            sum += voice.process();
+    /// Render one stereo sample
+    pub fn process(&mut self) -> (f32, f32) {
+        for (out, voice) in self.outputs.iter_mut().zip(&mut self.voices) {
+            *out = voice.process();
+        }
+
+        // LEVEL applies to a lane whether it plays a sample or the synthesized voice
+        for (((pad, voice), lane), out) in self
+            .pads
+            .iter()
+            .zip(&mut self.pad_voices)
+            .zip(&self.voices)
+            .zip(&mut self.outputs)
+        {
+            let Some(pad) = pad else {
+                continue;
+            };
+            if !voice.active {
+                continue;
+            }
+            if voice.position >= pad.sample.len() as f64 || voice.envelope < 0.001 {
+                voice.active = false;
+                continue;
+            }
+            let gain = voice.envelope * pad.gain * lane.level;
+            *out += cubic_interpolate(&pad.sample, voice.position) * gain;
+            voice.position += voice.rate;
+            voice.envelope *= voice.decay;
+        }
+
+        let (mut left, mut right) = (0.0f32, 0.0f32);
+        for lane in 0..self.outputs.len() {
+            self.outputs[lane] = if self.mutes[lane] { 0.0 } else { self.outputs[lane] * 0.5 };
+            let (l, r) = self.lane_stereo(lane);
+            left += l;
+            right += r;
+        }
+        (left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::sample_kit::PadConfig;
+    use std::sync::Arc;
+
+    fn pad(len: usize) -> SamplePad {
+        SamplePad {
+            sample: Arc::from(vec![0.5; len]),
+            rate: 1.0,
+            decay: 1.0,
+            gain: 1.0,
+            choke: None,
+            config: PadConfig {
+                file: "test.wav".to_string(),
+                pitch: 0.0,
+                decay: 0.0,
+                gain: 1.0,
+                choke: None,
+            },
+        }
+    }
+
+    /// Samples until a lane falls silent after a hit
+    fn hit_length(kit: &mut DrumKit, instrument: usize) -> usize {
+        kit.trigger(instrument);
+        (0..10_000).take_while(|_| {
+            kit.process();
+            kit.lane_output(instrument) != 0.0
+        }).count()
+    }
+
+    #[test]
+    fn decay_is_the_time_to_fall_60_db() {
+        let mut kit = DrumKit::new();
+        kit.set_param(2, 1, 100.0);
+        assert_eq!(kit.params()[2][1], 100.0);
+
+        let samples = hit_length(&mut kit, 2);
+        let ms = samples as f32 / SAMPLE_RATE as f32 * 1000.0;
+        assert!((ms - 100.0).abs() < 1.0, "{ms} ms");
+    }
+
+    #[test]
+    fn params_stay_in_range_when_stepped() {
+        for index in 0..DRUM_PARAM_COUNT {
+            let top = (0..1000).fold(0.5, |v, _| step_param(index, v, true));
+            let bottom = (0..1000).fold(0.5, |v, _| step_param(index, v, false));
+            assert_eq!(clamp_param(index, top), top);
+            assert_eq!(clamp_param(index, bottom), bottom);
+            assert!(bottom < top);
+        }
+    }
+
+    #[test]
+    fn set_kit_swaps_pads_and_returns_the_old_ones() {
+        let mut kit = DrumKit::new();
+        let old = kit.set_kit(DrumKit::lane_pads(Some(SampleKit {
+            name: "TEST".to_string(),
+            pads: vec![Some(pad(100))],
+        })));
+        assert!(old.iter().all(Option::is_none));
+        assert_eq!(hit_length(&mut kit, 0), 100);
+
+        // A pad list that doesn't fit the lanes is refused
+        let refused = kit.set_kit(vec![None]);
+        assert_eq!(refused.len(), 1);
+
+        let old = kit.set_kit(DrumKit::lane_pads(None));
+        assert!(old[0].is_some());
+        assert!(hit_length(&mut kit, 0) > 100);
+    }
+
+    #[test]
+    fn tune_shifts_sample_pads() {
+        let mut kit = DrumKit::new();
+        kit.set_kit(DrumKit::lane_pads(Some(SampleKit {
+            name: "TEST".to_string(),
+            pads: vec![Some(pad(1000))],
+        })));
+        kit.set_param(0, 0, 12.0);
+        assert_eq!(hit_length(&mut kit, 0), 500);
+
+        // A TUNE lock shifts the pad for one hit, as it does a synthesized voice
+        let mut locks = [None; DRUM_PARAM_COUNT];
+        locks[0] = Some(-12.0);
+        kit.apply_locks(0, &locks);
+        kit.trigger_velocity(0, 1.0);
+        let locked = (0..10_000).take_while(|_| {
+            kit.process();
+            kit.lane_output(0) != 0.0
+        }).count();
+        assert_eq!(locked, 2000);
+        assert_eq!(hit_length(&mut kit, 0), 500);
+    }
+
+    #[test]
+    fn choked_pads_fade_out() {
+        let mut kit = DrumKit::new();
+        let choked = SamplePad { choke: Some(1), ..pad(5000) };
+        kit.set_kit(DrumKit::lane_pads(Some(SampleKit {
+            name: "TEST".to_string(),
+            pads: vec![None, None, Some(choked.clone()), Some(choked)],
+        })));
+        kit.trigger(2);
+        kit.trigger(3);
+        for _ in 0..1000 {
+            kit.process();
         }
-        sum * 0.5
+        assert_eq!(kit.lane_output(2), 0.0);
+        assert!(kit.lane_output(3) > 0.0);
     }
 }
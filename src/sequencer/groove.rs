@@ -0,0 +1,243 @@
+use crate::constants::SAMPLE_RATE;
+use crate::sequencer::drum_kit::{DrumKit, DRUM_PARAM_COUNT};
+use crate::sequencer::pattern::{DrumPattern, LANE_COUNT, MAX_RATCHETS, MAX_STEPS};
+
+/// Largest per-step nudge, as a fraction of a step either way
+pub const MAX_NUDGE: f32 = 0.5;
+/// Swing range: 0.5 is straight, 0.75 pushes off-beats halfway to the next step
+pub const MIN_SWING: f32 = 0.5;
+pub const MAX_SWING: f32 = 0.75;
+/// Timing jitter at full humanize, either way
+const MAX_JITTER_MS: f32 = 15.0;
+/// Velocity drop at full humanize
+const MAX_VELOCITY_JITTER: f32 = 0.4;
+/// Triggers that can be waiting at once: every hit of a full pattern, far more
+/// than the lookahead ever holds, so scheduling never allocates
+const MAX_PENDING: usize = MAX_STEPS * LANE_COUNT * MAX_RATCHETS as usize;
+
+/// Offset of a step from the grid, in steps, from swing and its nudge
+pub fn step_offset(step: usize, swing: f32, nudge: f32) -> f32 {
+    let swing = if step % 2 == 1 { (swing - 0.5) * 2.0 } else { 0.0 };
+    swing + nudge.clamp(-MAX_NUDGE, MAX_NUDGE)
+}
+
+struct PendingTrigger {
+    lane: usize,
+    locks: [Option<f32>; DRUM_PARAM_COUNT],
+    /// Timeline sample to fire at
+    at: usize,
+    /// Timeline sample of the step's grid position
+    grid: usize,
+    velocity: f32,
+}
+
+/// Schedules drum steps a little ahead of the playhead so swing, nudge and
+/// humanize can move them off the grid in either direction, to the sample
+pub struct Groove {
+    swing: f32,
+    humanize: f32,
+    /// Next pattern step to schedule
+    next_step: usize,
+    /// Step length `next_step` was counted in
+    step_samples: f64,
+    pending: Vec<PendingTrigger>,
+    /// Grid position of each lane's last fired step, so a restart doesn't play it twice
+    fired: [Option<usize>; LANE_COUNT],
+    rng_state: u32,
+}
+
+impl Groove {
+    pub fn new() -> Self {
+        Self {
+            swing: MIN_SWING,
+            humanize: 0.0,
+            next_step: 0,
+            step_samples: 0.0,
+            pending: Vec::with_capacity(MAX_PENDING),
+            fired: [None; LANE_COUNT],
+            rng_state: 33333,
+        }
+    }
+
+    pub fn set_swing(&mut self, swing: f32) {
+        self.swing = swing.clamp(MIN_SWING, MAX_SWING);
+    }
+
+    pub fn set_humanize(&mut self, amount: f32) {
+        self.humanize = amount.clamp(0.0, 1.0);
+    }
+
+    /// A pattern (re)starts at `pattern_start`: drop anything scheduled from there on
+    /// and resume at the first step from `position`. Steps that already fired early,
+    /// ahead of the restart, are left to finish rather than played again.
+    pub fn restart(&mut self, pattern: &DrumPattern, pattern_start: usize, position: usize, bpm: f32) {
+        let fired = self.fired;
+        self.pending
+            .retain(|t| t.grid < pattern_start || fired.get(t.lane).is_some_and(|g| *g == Some(t.grid)));
+        self.step_samples = pattern.step_samples(bpm);
+        self.next_step = first_step(pattern_start, position, self.step_samples);
+    }
+
+    /// The timeline jumped: drop all pending triggers
+    pub fn seek(&mut self, pattern: &DrumPattern, pattern_start: usize, position: usize, bpm: f32) {
+        self.pending.clear();
+        self.fired = [None; LANE_COUNT];
+        self.restart(pattern, pattern_start, position, bpm);
+    }
+
+    /// Queue the pattern's steps that fall within the lookahead of `position`
+    pub fn schedule(&mut self, pattern: &DrumPattern, pattern_start: usize, position: usize, bpm: f32) {
+        let step_samples = pattern.step_samples(bpm);
+        let jitter_samples = (MAX_JITTER_MS / 1000.0 * SAMPLE_RATE as f32) as f64;
+        let lookahead = step_samples * MAX_NUDGE as f64 + jitter_samples;
+        if step_samples != self.step_samples {
+            // Tempo or resolution changed: carry on past what is already queued, on the new grid
+            self.step_samples = step_samples;
+            self.next_step = first_step(pattern_start, position + lookahead as usize, step_samples);
+        }
+
+        loop {
+            let grid = pattern_start as f64 + self.next_step as f64 * step_samples;
+            if grid - lookahead > position as f64 {
+                break;
+            }
+            let step = self.next_step;
+            self.next_step += 1;
+
+            for (lane, steps) in pattern.lanes.iter().enumerate() {
+                let Some(s) = steps.step(pattern.lane_step(lane, step)) else {
+                    continue;
+                };
+                if self.fired.get(lane).is_some_and(|g| g.is_some_and(|g| g >= grid as usize)) {
+                    continue;
+                }
+                if !s.active || (s.probability < 1.0 && self.random() >= s.probability) {
+                    continue;
+                }
+                let jitter = self.random() * 2.0 - 1.0;
+                let offset = step_offset(step, self.swing, s.nudge) as f64 * step_samples
+                    + (jitter * self.humanize) as f64 * jitter_samples;
+                let velocity = s.velocity * (1.0 - self.random() * self.humanize * MAX_VELOCITY_JITTER);
+                let ratchets = s.ratchets.clamp(1, MAX_RATCHETS);
+                for hit in 0..ratchets {
+                    if self.pending.len() == MAX_PENDING {
+                        break;
+                    }
+                    let at = grid + offset + step_samples * hit as f64 / ratchets as f64;
+                    self.pending.push(PendingTrigger {
+                        lane,
+                        locks: s.locks,
+                        at: (at.max(0.0) as usize).max(position),
+                        grid: grid as usize,
+                        velocity,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Write a live hit on `lane` at `position` into `pattern`, on the nearest step.
+    /// `quantize` (0-1) is how far the hit is pulled onto the grid; the rest of its
+    /// distance from the step is kept as the step's nudge. Returns (step, nudge).
+    pub fn record_hit(
+        &mut self,
+        pattern: &mut DrumPattern,
+        lane: usize,
+        pattern_start: usize,
+        position: usize,
+        bpm: f32,
+        quantize: f32,
+    ) -> Option<(usize, f32)> {
+        let step_samples = pattern.step_samples(bpm);
+        let exact = position.saturating_sub(pattern_start) as f64 / step_samples;
+        let nearest = exact.round() as usize;
+        let residual = (exact - nearest as f64) as f32;
+        let step = pattern.lane_step(lane, nearest);
+        let nudge = residual * (1.0 - quantize.clamp(0.0, 1.0));
+
+        let settings = pattern.lanes.get_mut(lane)?.step_mut(step)?;
+        settings.active = true;
+        settings.set_nudge(nudge);
+
+        // The hit was just played live, so don't play the step again this time round
+        let grid = (pattern_start as f64 + nearest as f64 * step_samples) as usize;
+        self.pending.retain(|t| t.lane != lane || t.grid != grid);
+        Some((step, settings.nudge))
+    }
+
+    /// Fire the triggers due at `position`
+    pub fn fire(&mut self, position: usize, kit: &mut DrumKit) {
+        self.pending.retain(|t| {
+            if t.at > position {
+                return true;
+            }
+            kit.apply_locks(t.lane, &t.locks);
+            kit.trigger_velocity(t.lane, t.velocity);
+            if let Some(fired) = self.fired.get_mut(t.lane) {
+                *fired = Some(t.grid);
+            }
+            false
+        });
+    }
+
+    /// Random value in 0..1
+    fn random(&mut self) -> f32 {
+        self.rng_state = self.rng_state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (self.rng_state >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+/// First step at or after `position`
+fn first_step(pattern_start: usize, position: usize, step_samples: f64) -> usize {
+    (position.saturating_sub(pattern_start) as f64 / step_samples).ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swing_delays_off_beats_only() {
+        assert_eq!(step_offset(0, 0.75, 0.0), 0.0);
+        assert_eq!(step_offset(1, 0.75, 0.0), 0.5);
+        assert_eq!(step_offset(1, 0.5, -0.25), -0.25);
+        assert_eq!(step_offset(2, 0.6, 2.0), MAX_NUDGE);
+    }
+
+    #[test]
+    fn recorded_hits_land_on_the_nearest_step() {
+        let mut groove = Groove::new();
+        let mut pattern = DrumPattern::new();
+        // 120 BPM 16ths: 5512.5 samples per step; hit 0.2 steps early for step 2
+        let position = (1.8 * 5512.5) as usize;
+        let (step, nudge) = groove.record_hit(&mut pattern, 1, 0, position, 120.0, 0.5).unwrap();
+        assert_eq!(step, 2);
+        assert!(pattern.is_active(1, 2));
+        assert!((nudge + 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn restart_does_not_replay_a_step_that_fired_early() {
+        let mut groove = Groove::new();
+        let mut kit = DrumKit::new();
+        let mut pattern = DrumPattern::new();
+        pattern.lanes[0].toggle(0);
+        pattern.lanes[0].steps[0].set_nudge(-0.25);
+        // 120 BPM 16ths: one 16-step pass is 88200 samples
+        let pass = 88200;
+
+        let mut hits = 0;
+        let mut pattern_start = 0;
+        for position in 0..pass * 2 {
+            if position == pass {
+                // The same pattern queued again takes over on the downbeat
+                pattern_start = pass;
+                groove.restart(&pattern, pattern_start, position, 120.0);
+            }
+            groove.schedule(&pattern, pattern_start, position, 120.0);
+            hits += groove.pending.iter().filter(|t| t.at <= position).count();
+            groove.fire(position, &mut kit);
+        }
+        assert_eq!(hits, 2);
+    }
+}
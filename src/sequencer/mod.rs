@@ -1,3 +1,6 @@
 pub mod pattern;
 pub mod drum_kit;
 pub mod clock;
+pub mod groove;
+pub mod note_pattern;
+pub mod sample_kit;
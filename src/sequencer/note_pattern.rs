@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::synth::SynthEngine;
+
+pub const STEPS_PER_BAR: usize = 16;
+pub const MAX_BARS: usize = 8;
+/// Longest note, in steps
+pub const MAX_NOTE_LENGTH: u8 = 64;
+/// Most notes a player holds at once; the oldest is released to make room
+const MAX_HELD: usize = 16;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Note name with octave, using C4 = MIDI 60
+pub fn note_name(note: u8) -> String {
+    format!("{}{}", NOTE_NAMES[note as usize % 12], note as i32 / 12 - 1)
+}
+
+/// A note starting on a sequencer step
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct NoteStep {
+    pub note: u8,
+    /// Length in steps
+    pub length: u8,
+    pub velocity: f32,
+}
+
+/// Melodic pattern of one or more 16-step bars, at most one note per step
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NotePattern {
+    pub steps: Vec<Option<NoteStep>>,
+}
+
+impl Default for NotePattern {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotePattern {
+    pub fn new() -> Self {
+        Self {
+            steps: vec![None; STEPS_PER_BAR],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn bars(&self) -> usize {
+        self.steps.len() / STEPS_PER_BAR
+    }
+
+    pub fn get(&self, step: usize) -> Option<NoteStep> {
+        self.steps.get(step).copied().flatten()
+    }
+
+    /// Place `note` on a step, or remove it if that pitch is already there
+    pub fn toggle(&mut self, step: usize, note: u8, velocity: f32) {
+        if let Some(slot) = self.steps.get_mut(step) {
+            *slot = match slot {
+                Some(existing) if existing.note == note => None,
+                _ => Some(NoteStep {
+                    note,
+                    length: 1,
+                    velocity,
+                }),
+            };
+        }
+    }
+
+    pub fn adjust_length(&mut self, step: usize, delta: i32) {
+        if let Some(Some(n)) = self.steps.get_mut(step) {
+            n.length = (n.length as i32 + delta).clamp(1, MAX_NOTE_LENGTH as i32) as u8;
+        }
+    }
+
+    pub fn adjust_velocity(&mut self, step: usize, delta: f32) {
+        if let Some(Some(n)) = self.steps.get_mut(step) {
+            n.velocity = (n.velocity + delta).clamp(0.05, 1.0);
+        }
+    }
+
+    pub fn add_bar(&mut self) {
+        if self.bars() < MAX_BARS {
+            self.steps.extend([None; STEPS_PER_BAR]);
+        }
+    }
+
+    pub fn remove_bar(&mut self) {
+        if self.bars() > 1 {
+            self.steps.truncate(self.steps.len() - STEPS_PER_BAR);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.iter_mut().for_each(|s| *s = None);
+    }
+
+    /// Whole bars only, between 1 and `MAX_BARS` (for patterns read from disk)
+    pub fn normalize(&mut self) {
+        let bars = self.steps.len().div_ceil(STEPS_PER_BAR).clamp(1, MAX_BARS);
+        self.steps.resize(bars * STEPS_PER_BAR, None);
+        for n in self.steps.iter_mut().flatten() {
+            n.note = n.note.min(127);
+            n.length = n.length.clamp(1, MAX_NOTE_LENGTH);
+            n.velocity = n.velocity.clamp(0.05, 1.0);
+        }
+    }
+}
+
+/// Plays a `NotePattern` into a synth engine, holding each note for its length
+pub struct NotePlayer {
+    pub pattern: NotePattern,
+    /// Sounding notes, oldest first, and the steps they have left. Never grows
+    /// past its capacity, so the audio thread doesn't allocate.
+    held: Vec<(u8, usize)>,
+}
+
+impl NotePlayer {
+    pub fn new() -> Self {
+        Self {
+            pattern: NotePattern::new(),
+            held: Vec::with_capacity(MAX_HELD),
+        }
+    }
+
+    /// Advance to `step` (counted from the clock start), ending and starting notes
+    pub fn step(&mut self, step: usize, engine: &mut dyn SynthEngine) {
+        self.held.retain_mut(|(note, remaining)| {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                engine.note_off(*note);
+            }
+            *remaining > 0
+        });
+
+        if self.pattern.is_empty() {
+            return;
+        }
+        if let Some(n) = self.pattern.get(step % self.pattern.len()) {
+            // Retrigger rather than stack the same pitch
+            if let Some(i) = self.held.iter().position(|(note, _)| *note == n.note) {
+                self.held.remove(i);
+                engine.note_off(n.note);
+            }
+            if self.held.len() == MAX_HELD {
+                let (oldest, _) = self.held.remove(0);
+                engine.note_off(oldest);
+            }
+            engine.note_on(n.note, n.velocity);
+            self.held.push((n.note, n.length.max(1) as usize));
+        }
+    }
+
+    pub fn release_all(&mut self, engine: &mut dyn SynthEngine) {
+        for (note, _) in self.held.drain(..) {
+            engine.note_off(note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Engine that records note on/off events
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<(bool, u8)>,
+    }
+
+    impl SynthEngine for Recorder {
+        fn note_on(&mut self, note: u8, _velocity: f32) { self.events.push((true, note)); }
+        fn note_off(&mut self, note: u8) { self.events.push((false, note)); }
+        fn process(&mut self, _output: &mut [f32]) {}
+        fn set_param(&mut self, _index: usize, _value: f32) {}
+        fn get_param(&self, _index: usize) -> f32 { 0.0 }
+        fn param_count(&self) -> usize { 0 }
+        fn param_name(&self, _index: usize) -> &str { "" }
+        fn name(&self) -> &str { "TEST" }
+    }
+
+    #[test]
+    fn notes_are_held_for_their_length() {
+        let mut player = NotePlayer::new();
+        player.pattern.toggle(0, 60, 1.0);
+        player.pattern.adjust_length(0, 2);
+        let mut engine = Recorder::default();
+
+        for step in 0..4 {
+            player.step(step, &mut engine);
+        }
+        assert_eq!(engine.events, vec![(true, 60), (false, 60)]);
+        // Released on the fourth step, after three steps of sound
+        player.step(16, &mut engine);
+        assert_eq!(engine.events.last(), Some(&(true, 60)));
+    }
+
+    #[test]
+    fn overlapping_notes_release_the_oldest_without_growing() {
+        let mut player = NotePlayer::new();
+        for step in 0..STEPS_PER_BAR {
+            player.pattern.toggle(step, 40 + step as u8, 1.0);
+            player.pattern.adjust_length(step, MAX_NOTE_LENGTH as i32);
+        }
+        let mut engine = Recorder::default();
+
+        for step in 0..STEPS_PER_BAR * 2 {
+            player.step(step, &mut engine);
+        }
+        assert!(player.held.len() <= MAX_HELD);
+        assert_eq!(player.held.capacity(), MAX_HELD);
+        assert_eq!(engine.events.iter().filter(|(on, _)| !on).count(), STEPS_PER_BAR * 2 - MAX_HELD);
+    }
+}
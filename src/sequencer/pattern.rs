@@ -1,34 +1,343 @@
-/// 16-step pattern for a single instrument
-#[derive(Clone)]
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::constants::SAMPLE_RATE;
+use crate::sequencer::drum_kit::DRUM_PARAM_COUNT;
+use crate::sequencer::groove::MAX_NUDGE;
+
+/// Drum patterns per project
+pub const PATTERN_COUNT: usize = 16;
+/// Drum lanes (instruments) per pattern
+pub const LANE_COUNT: usize = 6;
+/// Longest pattern or lane, in steps
+pub const MAX_STEPS: usize = 64;
+/// Length of a new pattern
+pub const DEFAULT_LENGTH: usize = 16;
+/// Most hits a step can roll
+pub const MAX_RATCHETS: u8 = 4;
+
+/// Pattern letter shown in the UI (A-P)
+pub fn pattern_label(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+/// Note value of one sequencer step
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    Eighth,
+    #[default]
+    Sixteenth,
+    /// 16th-note triplets
+    Triplet,
+    ThirtySecond,
+}
+
+impl Resolution {
+    pub fn next(self) -> Self {
+        match self {
+            Resolution::Eighth => Resolution::Sixteenth,
+            Resolution::Sixteenth => Resolution::Triplet,
+            Resolution::Triplet => Resolution::ThirtySecond,
+            Resolution::ThirtySecond => Resolution::Eighth,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Resolution::Eighth => "1/8",
+            Resolution::Sixteenth => "1/16",
+            Resolution::Triplet => "1/16T",
+            Resolution::ThirtySecond => "1/32",
+        }
+    }
+
+    /// Steps per quarter note
+    pub fn steps_per_beat(self) -> usize {
+        match self {
+            Resolution::Eighth => 2,
+            Resolution::Sixteenth => 4,
+            Resolution::Triplet => 6,
+            Resolution::ThirtySecond => 8,
+        }
+    }
+}
+
+/// One step of a lane
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub active: bool,
+    #[serde(default = "full")]
+    pub velocity: f32,
+    /// Chance the step plays each time round, 0-1
+    #[serde(default = "full")]
+    pub probability: f32,
+    /// Hits spread evenly across the step (rolls)
+    #[serde(default = "single")]
+    pub ratchets: u8,
+    /// Timing offset, as a fraction of a step
+    #[serde(default)]
+    pub nudge: f32,
+    /// Drum voice parameters for this step only, in `DRUM_PARAM_NAMES` order
+    #[serde(default, deserialize_with = "deserialize_locks")]
+    pub locks: [Option<f32>; DRUM_PARAM_COUNT],
+}
+
+fn full() -> f32 {
+    1.0
+}
+
+fn single() -> u8 {
+    1
+}
+
+impl Default for Step {
+    fn default() -> Self {
+        Self {
+            active: false,
+            velocity: 1.0,
+            probability: 1.0,
+            ratchets: 1,
+            nudge: 0.0,
+            locks: [None; DRUM_PARAM_COUNT],
+        }
+    }
+}
+
+impl Step {
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(0.1, 1.0);
+    }
+
+    pub fn set_probability(&mut self, probability: f32) {
+        self.probability = probability.clamp(0.0, 1.0);
+    }
+
+    /// Next ratchet count, wrapping back to a single hit
+    pub fn cycle_ratchets(&mut self) {
+        self.ratchets = self.ratchets % MAX_RATCHETS + 1;
+    }
+
+    pub fn set_nudge(&mut self, nudge: f32) {
+        self.nudge = nudge.clamp(-MAX_NUDGE, MAX_NUDGE);
+    }
+
+    pub fn has_locks(&self) -> bool {
+        self.locks.iter().any(Option::is_some)
+    }
+}
+
+/// Locks saved with fewer (or more) drum parameters than there are now
+fn deserialize_locks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[Option<f32>; DRUM_PARAM_COUNT], D::Error> {
+    let saved = Vec::<Option<f32>>::deserialize(deserializer)?;
+    Ok(std::array::from_fn(|i| saved.get(i).copied().flatten()))
+}
+
+/// Steps for a single instrument
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pattern {
-    pub steps: [bool; 16],
+    pub steps: Vec<Step>,
+    /// Lane length for polymeters; `None` follows the pattern length
+    pub length: Option<usize>,
 }
 
 impl Pattern {
     pub fn new() -> Self {
-        Self { steps: [false; 16] }
+        Self {
+            steps: vec![Step::default(); MAX_STEPS],
+            length: None,
+        }
+    }
+
+    pub fn step(&self, step: usize) -> Option<&Step> {
+        self.steps.get(step)
+    }
+
+    pub fn step_mut(&mut self, step: usize) -> Option<&mut Step> {
+        self.steps.get_mut(step)
     }
 
     pub fn toggle(&mut self, step: usize) {
-        if step < 16 {
-            self.steps[step] = !self.steps[step];
+        if let Some(s) = self.steps.get_mut(step) {
+            s.active = !s.active;
         }
     }
 
     pub fn is_active(&self, step: usize) -> bool {
-        step < 16 && self.steps[step]
+        self.steps.get(step).is_some_and(|s| s.active)
+    }
+}
+
+fn default_length() -> usize {
+    DEFAULT_LENGTH
+}
+
+/// One drum pattern: a lane for every instrument
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrumPattern {
+    pub lanes: Vec<Pattern>,
+    /// Steps before the pattern loops
+    #[serde(default = "default_length")]
+    pub length: usize,
+    #[serde(default)]
+    pub resolution: Resolution,
+}
+
+impl DrumPattern {
+    pub fn new() -> Self {
+        Self {
+            lanes: (0..LANE_COUNT).map(|_| Pattern::new()).collect(),
+            length: DEFAULT_LENGTH,
+            resolution: Resolution::default(),
+        }
+    }
+
+    pub fn is_active(&self, lane: usize, step: usize) -> bool {
+        self.lanes.get(lane).is_some_and(|p| p.is_active(step))
+    }
+
+    pub fn lane_length(&self, lane: usize) -> usize {
+        self.lanes
+            .get(lane)
+            .and_then(|p| p.length)
+            .unwrap_or(self.length)
+            .clamp(1, MAX_STEPS)
+    }
+
+    /// Longest lane, which sets how many steps the editor shows
+    pub fn max_length(&self) -> usize {
+        (0..self.lanes.len()).map(|l| self.lane_length(l)).fold(self.length, usize::max)
+    }
+
+    /// Step a lane plays at `step` steps into the pattern
+    pub fn lane_step(&self, lane: usize, step: usize) -> usize {
+        step % self.lane_length(lane)
+    }
+
+    /// Samples in one step at `bpm`
+    pub fn step_samples(&self, bpm: f32) -> f64 {
+        let samples_per_beat = SAMPLE_RATE as f64 * 60.0 / bpm.clamp(40.0, 300.0) as f64;
+        samples_per_beat / self.resolution.steps_per_beat() as f64
+    }
+
+    /// Samples to play the pattern through once at `bpm`
+    pub fn duration(&self, bpm: f32) -> f64 {
+        self.step_samples(bpm) * self.length.clamp(1, MAX_STEPS) as f64
+    }
+
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length.clamp(1, MAX_STEPS);
+    }
+
+    /// Set a lane's own length; matching the pattern length makes it follow the pattern again
+    pub fn set_lane_length(&mut self, lane: usize, length: usize) {
+        let length = length.clamp(1, MAX_STEPS);
+        if let Some(p) = self.lanes.get_mut(lane) {
+            p.length = (length != self.length).then_some(length);
+        }
+    }
+
+    /// Exactly `LANE_COUNT` lanes of `MAX_STEPS` steps (for patterns read from disk)
+    pub fn normalize(&mut self) {
+        self.lanes.resize_with(LANE_COUNT, Pattern::new);
+        self.length = self.length.clamp(1, MAX_STEPS);
+        for lane in &mut self.lanes {
+            lane.steps.resize(MAX_STEPS, Step::default());
+            for step in &mut lane.steps {
+                step.set_velocity(step.velocity);
+                step.set_probability(step.probability);
+                step.ratchets = step.ratchets.clamp(1, MAX_RATCHETS);
+                step.set_nudge(step.nudge);
+            }
+            lane.length = lane.length.map(|l| l.clamp(1, MAX_STEPS));
+        }
     }
 }
 
-/// Collection of patterns for all instruments
+/// Collection of drum patterns for a project
 pub struct PatternBank {
-    pub patterns: Vec<Pattern>,
+    pub patterns: Vec<DrumPattern>,
 }
 
 impl PatternBank {
-    pub fn new(instrument_count: usize) -> Self {
+    pub fn new() -> Self {
         Self {
-            patterns: (0..instrument_count).map(|_| Pattern::new()).collect(),
+            patterns: (0..PATTERN_COUNT).map(|_| DrumPattern::new()).collect(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&DrumPattern> {
+        self.patterns.get(index)
+    }
+}
+
+/// Song chain entry playing at `position` on the tape timeline, with the samples it
+/// starts and ends at. Each entry plays its pattern through once; the chain loops.
+pub fn song_entry(song: &[usize], bank: &PatternBank, position: usize, bpm: f32) -> Option<(usize, usize, usize)> {
+    let duration = |pattern: usize| bank.get(pattern).map_or(0.0, |p| p.duration(bpm));
+    let lap: f64 = song.iter().map(|p| duration(*p)).sum();
+    if lap <= 0.0 {
+        return None;
+    }
+
+    let position = position as f64;
+    let mut start = (position / lap).floor() * lap;
+    for (entry, pattern) in song.iter().enumerate() {
+        let end = start + duration(*pattern);
+        // The last entry takes whatever rounding leaves at the end of the lap
+        if position < end || entry == song.len() - 1 {
+            return Some((entry, start.ceil() as usize, end.ceil() as usize));
         }
+        start = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lanes_loop_at_their_own_length() {
+        let mut pattern = DrumPattern::new();
+        pattern.set_length(16);
+        pattern.set_lane_length(1, 12);
+        assert_eq!(pattern.lane_step(0, 17), 1);
+        assert_eq!(pattern.lane_step(1, 17), 5);
+
+        // Back to the pattern length follows the pattern again
+        pattern.set_lane_length(1, 16);
+        pattern.set_length(8);
+        assert_eq!(pattern.lane_length(1), 8);
+    }
+
+    #[test]
+    fn song_entries_last_their_own_pattern_length() {
+        let mut bank = PatternBank::new();
+        bank.patterns[1].set_length(24);
+        // 120 BPM 16ths: 5512.5 samples per step
+        let song = [0, 1, 0];
+        assert_eq!(song_entry(&[], &bank, 1000, 120.0), None);
+        assert_eq!(song_entry(&song, &bank, 16 * 5512, 120.0), Some((0, 0, 88200)));
+        assert_eq!(song_entry(&song, &bank, 88200, 120.0), Some((1, 88200, 220500)));
+        assert_eq!(song_entry(&song, &bank, 30 * 5513, 120.0), Some((1, 88200, 220500)));
+        // Around the loop again
+        assert_eq!(song_entry(&song, &bank, 56 * 5513, 120.0), Some((0, 308700, 396900)));
+    }
+
+    #[test]
+    fn normalize_repairs_saved_patterns() {
+        let mut pattern: DrumPattern = serde_json::from_str(
+            r#"{"lanes": [{"steps": [{"active": true, "velocity": 3.0, "ratchets": 9, "nudge": -2.0}], "length": 200}], "length": 0}"#,
+        )
+        .unwrap();
+        pattern.normalize();
+
+        assert_eq!(pattern.lanes.len(), LANE_COUNT);
+        assert!(pattern.lanes.iter().all(|l| l.steps.len() == MAX_STEPS));
+        assert_eq!(pattern.length, 1);
+        assert_eq!(pattern.lane_length(0), MAX_STEPS);
+        let step = pattern.lanes[0].steps[0];
+        assert_eq!((step.velocity, step.ratchets, step.nudge), (1.0, MAX_RATCHETS, -MAX_NUDGE));
     }
 }
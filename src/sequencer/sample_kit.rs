@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SAMPLE_RATE;
+use crate::synth::engines::sampler::load_sample_file;
+
+/// Kit description file inside each kit directory
+pub const KIT_FILE: &str = "kit.json";
+
+/// One pad in `kit.json`. Pads map to sequencer lanes in order.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PadConfig {
+    /// WAV file, relative to the kit directory
+    pub file: String,
+    /// Pitch shift in semitones
+    #[serde(default)]
+    pub pitch: f32,
+    /// Time to fade out (-60 dB) in milliseconds; 0 plays the whole sample
+    #[serde(default)]
+    pub decay: f32,
+    #[serde(default = "default_gain")]
+    pub gain: f32,
+    /// Pads in the same choke group cut each other off (e.g. open/closed hat)
+    #[serde(default)]
+    pub choke: Option<u8>,
+}
+
+fn default_gain() -> f32 {
+    1.0
+}
+
+/// Contents of `kit.json`; a `null` pad keeps that lane's synthesized voice
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KitConfig {
+    pub name: String,
+    pub pads: Vec<Option<PadConfig>>,
+}
+
+/// A pad with its sample loaded, ready for the audio thread
+#[derive(Clone, Debug)]
+pub struct SamplePad {
+    pub sample: Arc<[f32]>,
+    /// Playback rate from the pitch shift
+    pub rate: f64,
+    /// Per-sample envelope multiplier (1.0 = no decay)
+    pub decay: f32,
+    pub gain: f32,
+    pub choke: Option<u8>,
+    /// Settings from `kit.json`, for display
+    pub config: PadConfig,
+}
+
+impl SamplePad {
+    fn from_config(config: &PadConfig, sample: Vec<f32>) -> Self {
+        let decay = if config.decay > 0.0 {
+            let samples = config.decay.max(1.0) / 1000.0 * SAMPLE_RATE as f32;
+            0.001f32.powf(1.0 / samples)
+        } else {
+            1.0
+        };
+        Self {
+            sample: sample.into(),
+            rate: 2.0f64.powf(config.pitch.clamp(-24.0, 24.0) as f64 / 12.0),
+            decay,
+            gain: config.gain.clamp(0.0, 2.0),
+            choke: config.choke,
+            config: config.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SampleKit {
+    pub name: String,
+    pub pads: Vec<Option<SamplePad>>,
+}
+
+/// Load `kit.json` and its samples from a kit directory
+pub fn load_kit(dir: &Path) -> Result<SampleKit, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(dir.join(KIT_FILE))?;
+    let config: KitConfig = serde_json::from_str(&json)?;
+
+    let mut pads = Vec::with_capacity(config.pads.len());
+    for pad in &config.pads {
+        pads.push(match pad {
+            Some(pad) => Some(SamplePad::from_config(pad, load_sample_file(&dir.join(&pad.file))?)),
+            None => None,
+        });
+    }
+
+    Ok(SampleKit {
+        name: config.name,
+        pads,
+    })
+}
+
+/// Subdirectories of `root` that contain a `kit.json`, sorted by name
+pub fn kit_dirs(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(KIT_FILE).is_file())
+        .collect();
+    dirs.sort();
+    dirs
+}
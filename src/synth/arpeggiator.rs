@@ -0,0 +1,268 @@
+use crate::constants::SAMPLE_RATE;
+use crate::synth::SynthEngine;
+
+/// Most notes the arpeggiator will hold at once
+const MAX_HELD: usize = 16;
+
+/// Note divisions as (label, length in beats)
+pub const ARP_RATES: [(&str, f32); 6] = [
+    ("1/4", 1.0),
+    ("1/8", 0.5),
+    ("1/8T", 1.0 / 3.0),
+    ("1/16", 0.25),
+    ("1/16T", 1.0 / 6.0),
+    ("1/32", 0.125),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArpMode {
+    Up,
+    Down,
+    UpDown,
+    Random,
+    /// Held notes in the order they were pressed
+    Played,
+}
+
+impl ArpMode {
+    pub fn next(self) -> Self {
+        match self {
+            ArpMode::Up => ArpMode::Down,
+            ArpMode::Down => ArpMode::UpDown,
+            ArpMode::UpDown => ArpMode::Random,
+            ArpMode::Random => ArpMode::Played,
+            ArpMode::Played => ArpMode::Up,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ArpMode::Up => "UP",
+            ArpMode::Down => "DOWN",
+            ArpMode::UpDown => "UP/DN",
+            ArpMode::Random => "RAND",
+            ArpMode::Played => "PLAYED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArpSettings {
+    pub enabled: bool,
+    pub mode: ArpMode,
+    /// Octave range, 1-4
+    pub octaves: u8,
+    /// Index into `ARP_RATES`
+    pub rate: usize,
+    /// Fraction of each step the note is held, 0.1-1.0
+    pub gate: f32,
+    /// Keep playing released notes until a new chord is pressed
+    pub latch: bool,
+}
+
+impl Default for ArpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: ArpMode::Up,
+            octaves: 1,
+            rate: 3,
+            gate: 0.5,
+            latch: false,
+        }
+    }
+}
+
+/// Turns held notes into a tempo-synced note pattern for one engine
+pub struct Arpeggiator {
+    settings: ArpSettings,
+    /// Notes the arpeggio plays, in the order they were pressed
+    played: Vec<u8>,
+    /// Same notes, lowest first
+    sorted: Vec<u8>,
+    /// Keys physically down; a latched chord is replaced once these all lift
+    pressed: Vec<u8>,
+    velocity: f32,
+    /// Arp steps played since notes were first held
+    position: usize,
+    last_tick: usize,
+    sounding: Option<u8>,
+    rng_state: u32,
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        Self {
+            settings: ArpSettings::default(),
+            played: Vec::with_capacity(MAX_HELD),
+            sorted: Vec::with_capacity(MAX_HELD),
+            pressed: Vec::with_capacity(MAX_HELD),
+            velocity: 0.8,
+            position: 0,
+            last_tick: usize::MAX,
+            sounding: None,
+            rng_state: 22222,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    pub fn is_sounding(&self, note: u8) -> bool {
+        self.sounding == Some(note)
+    }
+
+    pub fn set_settings(&mut self, settings: ArpSettings, engine: &mut dyn SynthEngine) {
+        let was_latched = self.settings.latch;
+        self.settings = ArpSettings {
+            octaves: settings.octaves.clamp(1, 4),
+            rate: settings.rate.min(ARP_RATES.len() - 1),
+            gate: settings.gate.clamp(0.1, 1.0),
+            ..settings
+        };
+
+        if !settings.enabled {
+            self.release(engine);
+            self.played.clear();
+            self.sorted.clear();
+            self.pressed.clear();
+        } else if was_latched && !settings.latch {
+            // Drop latched notes that are no longer held down
+            let pressed = &self.pressed;
+            self.played.retain(|n| pressed.contains(n));
+            self.sorted.retain(|n| pressed.contains(n));
+        }
+    }
+
+    pub fn note_on(&mut self, note: u8, velocity: f32) {
+        if self.settings.latch && self.pressed.is_empty() {
+            self.played.clear();
+            self.sorted.clear();
+        }
+        if self.played.is_empty() {
+            self.position = 0;
+        }
+        if !self.pressed.contains(&note) && self.pressed.len() < MAX_HELD {
+            self.pressed.push(note);
+        }
+        if !self.played.contains(&note) && self.played.len() < MAX_HELD {
+            self.played.push(note);
+            let at = self.sorted.partition_point(|n| *n < note);
+            self.sorted.insert(at, note);
+        }
+        self.velocity = velocity;
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        self.pressed.retain(|n| *n != note);
+        if !self.settings.latch {
+            self.played.retain(|n| *n != note);
+            self.sorted.retain(|n| *n != note);
+        }
+    }
+
+    /// Advance one sample at `sample_position` on the sequencer timeline
+    pub fn process(&mut self, sample_position: usize, bpm: f32, engine: &mut dyn SynthEngine) {
+        if !self.settings.enabled {
+            return;
+        }
+
+        let beats = ARP_RATES[self.settings.rate].1;
+        let samples_per_beat = SAMPLE_RATE as f32 * 60.0 / bpm.max(1.0);
+        let interval = ((samples_per_beat * beats) as usize).max(1);
+        let tick = sample_position / interval;
+
+        if tick != self.last_tick {
+            self.last_tick = tick;
+            self.release(engine);
+            if let Some(note) = self.note_at(self.position) {
+                engine.note_on(note, self.velocity);
+                self.sounding = Some(note);
+                self.position = self.position.wrapping_add(1);
+            }
+        } else if (sample_position % interval) as f32 >= interval as f32 * self.settings.gate {
+            self.release(engine);
+        }
+    }
+
+    fn release(&mut self, engine: &mut dyn SynthEngine) {
+        if let Some(note) = self.sounding.take() {
+            engine.note_off(note);
+        }
+    }
+
+    /// Note for arp step `position`, or `None` when nothing is held
+    fn note_at(&mut self, position: usize) -> Option<u8> {
+        let count = self.sorted.len();
+        let len = count * self.settings.octaves as usize;
+        if len == 0 {
+            return None;
+        }
+
+        let index = match self.settings.mode {
+            ArpMode::Up | ArpMode::Played => position % len,
+            ArpMode::Down => len - 1 - position % len,
+            ArpMode::UpDown => {
+                // Bounce without repeating the top and bottom notes
+                let period = (2 * len).saturating_sub(2).max(1);
+                let p = position % period;
+                if p < len { p } else { period - p }
+            }
+            ArpMode::Random => {
+                self.rng_state = self.rng_state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (self.rng_state >> 8) as usize % len
+            }
+        };
+
+        let notes = if self.settings.mode == ArpMode::Played {
+            &self.played
+        } else {
+            &self.sorted
+        };
+        let note = notes[index % count] as usize + 12 * (index / count);
+        Some(note.min(127) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arp(mode: ArpMode, octaves: u8, latch: bool) -> Arpeggiator {
+        let mut arp = Arpeggiator::new();
+        arp.settings = ArpSettings {
+            enabled: true,
+            mode,
+            octaves,
+            latch,
+            ..ArpSettings::default()
+        };
+        arp
+    }
+
+    #[test]
+    fn up_down_spans_octaves_without_repeating_ends() {
+        let mut arp = arp(ArpMode::UpDown, 2, false);
+        arp.note_on(64, 0.8);
+        arp.note_on(60, 0.8);
+        let notes: Vec<u8> = (0..8).filter_map(|i| arp.note_at(i)).collect();
+        assert_eq!(notes, vec![60, 64, 72, 76, 72, 64, 60, 64]);
+    }
+
+    #[test]
+    fn latch_holds_chord_until_next_press() {
+        let mut arp = arp(ArpMode::Played, 1, true);
+        arp.note_on(67, 0.8);
+        arp.note_on(60, 0.8);
+        arp.note_off(67);
+        arp.note_off(60);
+        assert_eq!(arp.note_at(0), Some(67));
+        assert_eq!(arp.note_at(1), Some(60));
+
+        // All keys were released, so a new press starts a new chord
+        arp.note_on(62, 0.8);
+        assert_eq!(arp.note_at(0), Some(62));
+        assert_eq!(arp.note_at(1), Some(62));
+    }
+}
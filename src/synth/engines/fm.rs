@@ -1,4 +1,6 @@
+use crate::audio::smoothing::SmoothedParam;
 use crate::constants::SAMPLE_RATE;
+use crate::synth::envelope::{self, Adsr, Envelope, ENV_PARAM_COUNT};
 use crate::synth::SynthEngine;
 
 const MAX_VOICES: usize = 8;
@@ -6,9 +8,8 @@ const MAX_VOICES: usize = 8;
 pub struct FmSynth {
     voices: [FmVoice; MAX_VOICES],
     ratio: f32,
-    mod_index: f32,
-    attack: f32,
-    decay: f32,
+    mod_index: SmoothedParam,
+    adsr: Adsr,
 }
 
 #[derive(Clone, Copy)]
@@ -16,10 +17,8 @@ struct FmVoice {
     carrier_phase: f64,
     mod_phase: f64,
     freq: f64,
-    active: bool,
-    envelope: f32,
+    envelope: Envelope,
     note: u8,
-    releasing: bool,
 }
 
 impl Default for FmVoice {
@@ -28,10 +27,8 @@ impl Default for FmVoice {
             carrier_phase: 0.0,
             mod_phase: 0.0,
             freq: 0.0,
-            active: false,
-            envelope: 0.0,
+            envelope: Envelope::default(),
             note: 0,
-            releasing: false,
         }
     }
 }
@@ -41,9 +38,8 @@ impl FmSynth {
         Self {
             voices: [FmVoice::default(); MAX_VOICES],
             ratio: 2.0,
-            mod_index: 1.5,
-            attack: 0.01,
-            decay: 0.8,
+            mod_index: SmoothedParam::new(1.5),
+            adsr: Adsr::new(0.01, 0.8, 1.0, 0.8),
         }
     }
 
@@ -54,49 +50,37 @@ impl FmSynth {
 
 impl SynthEngine for FmSynth {
     fn note_on(&mut self, note: u8, _velocity: f32) {
-        let slot = self.voices.iter().position(|v| !v.active).unwrap_or(0);
+        let slot = self.voices.iter().position(|v| !v.envelope.is_active()).unwrap_or(0);
         self.voices[slot] = FmVoice {
             carrier_phase: 0.0,
             mod_phase: 0.0,
             freq: Self::midi_to_freq(note),
-            active: true,
-            envelope: 0.0,
+            envelope: Envelope::triggered(),
             note,
-            releasing: false,
         };
     }
 
     fn note_off(&mut self, note: u8) {
         for v in &mut self.voices {
-            if v.active && v.note == note {
-                v.releasing = true;
+            if v.envelope.is_active() && v.note == note {
+                v.envelope.release();
             }
         }
     }
 
     fn process(&mut self, output: &mut [f32]) {
         let sr = SAMPLE_RATE as f64;
-        let attack_rate = 1.0 / (self.attack * SAMPLE_RATE as f32).max(1.0);
-        let decay_rate = 1.0 / (self.decay * SAMPLE_RATE as f32).max(1.0);
         let ratio = self.ratio as f64;
-        let mod_idx = self.mod_index as f64;
 
         for sample in output.iter_mut() {
+            let mod_idx = self.mod_index.tick() as f64;
             let mut sum = 0.0f32;
             for voice in &mut self.voices {
-                if !voice.active {
+                if !voice.envelope.is_active() {
                     continue;
                 }
 
-                if voice.releasing {
-                    voice.envelope -= decay_rate;
-                    if voice.envelope <= 0.0 {
-                        voice.active = false;
-                        continue;
-                    }
-                } else if voice.envelope < 1.0 {
-                    voice.envelope = (voice.envelope + attack_rate).min(1.0);
-                }
+                let env = voice.envelope.next(&self.adsr);
 
                 // 2-operator FM: carrier + modulator
                 let mod_freq = voice.freq * ratio;
@@ -104,7 +88,7 @@ impl SynthEngine for FmSynth {
                 let carrier_freq_mod = voice.freq + modulator * mod_idx * voice.freq;
                 let carrier = (voice.carrier_phase * std::f64::consts::TAU).sin() as f32;
 
-                sum += carrier * voice.envelope * 0.25;
+                sum += carrier * env * 0.25;
 
                 voice.carrier_phase += carrier_freq_mod / sr;
                 voice.mod_phase += mod_freq / sr;
@@ -118,21 +102,25 @@ impl SynthEngine for FmSynth {
     fn set_param(&mut self, index: usize, value: f32) {
         match index {
             0 => self.ratio = (value * 8.0).round().max(1.0),
-            1 => self.mod_index = value * 5.0,
-            2 => self.attack = value.clamp(0.001, 2.0),
-            3 => self.decay = value.clamp(0.01, 5.0),
-            _ => {}
+            1 => self.mod_index.set(value * 5.0),
+            _ => self.adsr.set_param(index - 2, value),
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.ratio / 8.0,
+            1 => self.mod_index.target() / 5.0,
+            _ => self.adsr.get_param(index - 2),
         }
     }
 
-    fn param_count(&self) -> usize { 4 }
+    fn param_count(&self) -> usize { 2 + ENV_PARAM_COUNT }
     fn param_name(&self, index: usize) -> &str {
         match index {
             0 => "RATIO",
             1 => "MOD IX",
-            2 => "ATTACK",
-            3 => "DECAY",
-            _ => "",
+            _ => envelope::param_name(index - 2),
         }
     }
     fn name(&self) -> &str { "FM" }
@@ -3,6 +3,7 @@ pub mod saw;
 pub mod fm;
 pub mod string;
 pub mod noise;
+pub mod sampler;
 
 use crate::synth::SynthEngine;
 
@@ -13,9 +14,76 @@ pub fn create_engine(index: usize) -> Box<dyn SynthEngine> {
         2 => Box::new(fm::FmSynth::new()),
         3 => Box::new(string::StringSynth::new()),
         4 => Box::new(noise::NoiseSynth::new()),
+        5 => Box::new(sampler::Sampler::new()),
         _ => Box::new(sine::SineSynth::new()),
     }
 }
 
-pub const ENGINE_COUNT: usize = 5;
-pub const ENGINE_NAMES: [&str; 5] = ["SINE", "SAW", "FM", "STRING", "NOISE"];
+/// Index in `ENGINE_NAMES` of the engine called `name`
+pub fn engine_index(name: &str) -> Option<usize> {
+    ENGINE_NAMES.iter().position(|n| n.eq_ignore_ascii_case(name))
+}
+
+/// Current values of all of an engine's parameters
+pub fn param_values(engine: &dyn SynthEngine) -> Vec<f32> {
+    (0..engine.param_count()).map(|i| engine.get_param(i)).collect()
+}
+
+/// Most parameters any engine has (the sampler's six plus the envelope)
+pub const MAX_PARAMS: usize = 11;
+
+/// Parameter values copied into a fixed array, for reporting from the audio
+/// thread without allocating
+#[derive(Debug, Clone, Copy)]
+pub struct ParamValues {
+    values: [f32; MAX_PARAMS],
+    count: usize,
+}
+
+impl ParamValues {
+    pub fn read(engine: &dyn SynthEngine) -> Self {
+        let count = engine.param_count().min(MAX_PARAMS);
+        let mut values = [0.0; MAX_PARAMS];
+        for (i, value) in values.iter_mut().take(count).enumerate() {
+            *value = engine.get_param(i);
+        }
+        Self { values, count }
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.values[..self.count]
+    }
+}
+
+/// Parameter names of the engine at `index`
+pub fn param_names(index: usize) -> Vec<String> {
+    let engine = create_engine(index);
+    (0..engine.param_count())
+        .map(|i| engine.param_name(i).to_string())
+        .collect()
+}
+
+/// Default parameter values of the engine at `index`
+pub fn default_params(index: usize) -> Vec<f32> {
+    param_values(create_engine(index).as_ref())
+}
+
+pub const ENGINE_COUNT: usize = 6;
+pub const ENGINE_NAMES: [&str; 6] = ["SINE", "SAW", "FM", "STRING", "NOISE", "SAMPLER"];
+/// Index of the sample-playback engine
+pub const SAMPLER_ENGINE: usize = 5;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_engine_fits_in_param_values() {
+        for index in 0..ENGINE_COUNT {
+            let engine = create_engine(index);
+            assert!(engine.param_count() <= MAX_PARAMS);
+            assert_eq!(engine_index(engine.name()), Some(index));
+            assert_eq!(ParamValues::read(engine.as_ref()).as_slice(), param_values(engine.as_ref()));
+        }
+    }
+}
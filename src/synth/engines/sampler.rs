@@ -0,0 +1,241 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::constants::SAMPLE_RATE;
+use crate::synth::envelope::{self, Adsr, Envelope, ENV_PARAM_COUNT};
+use crate::synth::SynthEngine;
+use crate::tape::speed::cubic_interpolate;
+
+const MAX_VOICES: usize = 8;
+const OWN_PARAMS: usize = 6;
+/// Lowest root note; the ROOT knob spans six octaves from here
+const ROOT_MIN: u8 = 24;
+const ROOT_RANGE: f32 = 72.0;
+/// Output samples a non-looping voice fades over as it reaches the region end,
+/// so samples that don't end at zero don't click
+const END_FADE: f64 = 0.002 * SAMPLE_RATE as f64;
+
+/// Plays a recorded or loaded sample pitched across the keyboard
+pub struct Sampler {
+    voices: [SamplerVoice; MAX_VOICES],
+    sample: Option<Arc<[f32]>>,
+    /// Note the sample plays back at its original pitch
+    root: u8,
+    // Playback region and loop, as fractions of the sample length
+    start: f32,
+    end: f32,
+    looping: bool,
+    loop_start: f32,
+    loop_end: f32,
+    adsr: Adsr,
+}
+
+#[derive(Clone, Copy, Default)]
+struct SamplerVoice {
+    position: f64,
+    rate: f64,
+    velocity: f32,
+    envelope: Envelope,
+    note: u8,
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        Self {
+            voices: [SamplerVoice::default(); MAX_VOICES],
+            sample: None,
+            root: 60,
+            start: 0.0,
+            end: 1.0,
+            looping: false,
+            loop_start: 0.0,
+            loop_end: 1.0,
+            adsr: Adsr::new(0.001, 0.5, 1.0, 0.2),
+        }
+    }
+
+    /// Sample indices for (start, end, loop start, loop end), with the loop kept inside the region
+    fn region(&self, len: usize) -> (f64, f64, f64, f64) {
+        let len = len.max(1) as f64;
+        let start = (self.start as f64 * len).min(len - 1.0);
+        let end = (self.end as f64 * len).clamp(start + 1.0, len);
+        let loop_start = (self.loop_start as f64 * len).clamp(start, end - 1.0);
+        let loop_end = (self.loop_end as f64 * len).clamp(loop_start + 1.0, end);
+        (start, end, loop_start, loop_end)
+    }
+}
+
+impl SynthEngine for Sampler {
+    fn note_on(&mut self, note: u8, velocity: f32) {
+        let Some(sample) = &self.sample else {
+            return;
+        };
+        let (start, ..) = self.region(sample.len());
+        let slot = self
+            .voices
+            .iter()
+            .position(|v| !v.envelope.is_active())
+            .unwrap_or(0);
+        self.voices[slot] = SamplerVoice {
+            position: start,
+            rate: 2.0f64.powf((note as f64 - self.root as f64) / 12.0),
+            velocity,
+            envelope: Envelope::triggered(),
+            note,
+        };
+    }
+
+    fn note_off(&mut self, note: u8) {
+        for v in &mut self.voices {
+            if v.envelope.is_active() && v.note == note {
+                v.envelope.release();
+            }
+        }
+    }
+
+    fn process(&mut self, output: &mut [f32]) {
+        let Some(sample) = &self.sample else {
+            return;
+        };
+        let (_, end, loop_start, loop_end) = self.region(sample.len());
+
+        for out in output.iter_mut() {
+            let mut sum = 0.0f32;
+            for voice in &mut self.voices {
+                if !voice.envelope.is_active() {
+                    continue;
+                }
+
+                let mut fade = 1.0;
+                if self.looping {
+                    while voice.position >= loop_end {
+                        voice.position -= loop_end - loop_start;
+                    }
+                } else if voice.position >= end {
+                    voice.envelope = Envelope::default();
+                    continue;
+                } else {
+                    fade = ((end - voice.position) / (END_FADE * voice.rate)).min(1.0) as f32;
+                }
+
+                let env = voice.envelope.next(&self.adsr);
+                sum += cubic_interpolate(sample, voice.position) * env * fade * voice.velocity * 0.6;
+                voice.position += voice.rate;
+            }
+            *out += sum;
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = value.clamp(0.0, 1.0);
+        match index {
+            0 => self.root = ROOT_MIN + (value * ROOT_RANGE).round() as u8,
+            1 => self.start = value,
+            2 => self.end = value,
+            3 => self.looping = value >= 0.5,
+            4 => self.loop_start = value,
+            5 => self.loop_end = value,
+            _ => self.adsr.set_param(index - OWN_PARAMS, value),
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => (self.root - ROOT_MIN) as f32 / ROOT_RANGE,
+            1 => self.start,
+            2 => self.end,
+            3 => if self.looping { 1.0 } else { 0.0 },
+            4 => self.loop_start,
+            5 => self.loop_end,
+            _ => self.adsr.get_param(index - OWN_PARAMS),
+        }
+    }
+
+    fn param_count(&self) -> usize { OWN_PARAMS + ENV_PARAM_COUNT }
+    fn param_name(&self, index: usize) -> &str {
+        match index {
+            0 => "ROOT",
+            1 => "START",
+            2 => "END",
+            3 => "LOOP",
+            4 => "LSTART",
+            5 => "LEND",
+            _ => envelope::param_name(index - OWN_PARAMS),
+        }
+    }
+    fn name(&self) -> &str { "SAMPLER" }
+
+    fn load_sample(&mut self, samples: Arc<[f32]>) -> Option<Arc<[f32]>> {
+        for v in &mut self.voices {
+            v.envelope = Envelope::default();
+        }
+        self.sample.replace(samples)
+    }
+}
+
+/// WAV files in `dir`, sorted by name
+pub fn sample_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Read a WAV as mono at the engine sample rate
+pub fn load_sample_file(path: &Path) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    let (samples, rate) = crate::project::load::read_wav_mono_f32(path)?;
+    if rate == SAMPLE_RATE || samples.is_empty() {
+        return Ok(samples);
+    }
+
+    let step = rate as f64 / SAMPLE_RATE as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    Ok((0..len).map(|i| cubic_interpolate(&samples, i as f64 * step)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_stays_valid_at_the_extremes() {
+        let mut sampler = Sampler::new();
+        let len = 100;
+        for value in [0.0, 1.0] {
+            for start in [0.0, 1.0] {
+                for looping in [0.0, 1.0] {
+                    sampler.set_param(1, start);
+                    sampler.set_param(2, value);
+                    sampler.set_param(3, looping);
+                    sampler.set_param(4, value);
+                    sampler.set_param(5, 1.0 - value);
+
+                    let (start, end, loop_start, loop_end) = sampler.region(len);
+                    assert!(start < end && end <= len as f64);
+                    assert!(start <= loop_start && loop_start < loop_end && loop_end <= end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fades_out_at_the_end_of_the_region() {
+        let mut sampler = Sampler::new();
+        sampler.load_sample(vec![1.0; SAMPLE_RATE as usize / 10].into());
+        sampler.set_param(2, 0.5);
+        sampler.note_on(sampler.root, 1.0);
+
+        let mut output = vec![0.0; SAMPLE_RATE as usize / 10];
+        sampler.process(&mut output);
+        let end = SAMPLE_RATE as usize / 20;
+        assert!(output[end - END_FADE as usize - 1] > 0.5);
+        assert!(output[end - 1] < 0.01);
+        assert!(output.windows(2).skip(100).all(|w| (w[1] - w[0]).abs() < 0.01));
+        assert!(output[end..].iter().all(|s| *s == 0.0));
+    }
+}
@@ -1,24 +1,23 @@
+use crate::audio::smoothing::SmoothedParam;
 use crate::constants::SAMPLE_RATE;
+use crate::synth::envelope::{self, Adsr, Envelope, ENV_PARAM_COUNT};
 use crate::synth::SynthEngine;
 
 const MAX_VOICES: usize = 8;
 
 pub struct SawSynth {
     voices: [SawVoice; MAX_VOICES],
-    cutoff: f32,
-    resonance: f32,
-    attack: f32,
-    decay: f32,
+    cutoff: SmoothedParam,
+    resonance: SmoothedParam,
+    adsr: Adsr,
 }
 
 #[derive(Clone, Copy)]
 struct SawVoice {
     phase: f64,
     freq: f64,
-    active: bool,
-    envelope: f32,
+    envelope: Envelope,
     note: u8,
-    releasing: bool,
     // Simple one-pole filter state
     filter_state: f32,
 }
@@ -28,10 +27,8 @@ impl Default for SawVoice {
         Self {
             phase: 0.0,
             freq: 0.0,
-            active: false,
-            envelope: 0.0,
+            envelope: Envelope::default(),
             note: 0,
-            releasing: false,
             filter_state: 0.0,
         }
     }
@@ -41,10 +38,9 @@ impl SawSynth {
     pub fn new() -> Self {
         Self {
             voices: [SawVoice::default(); MAX_VOICES],
-            cutoff: 0.5,
-            resonance: 0.3,
-            attack: 0.01,
-            decay: 0.5,
+            cutoff: SmoothedParam::new(0.5),
+            resonance: SmoothedParam::new(0.3),
+            adsr: Adsr::new(0.01, 0.5, 1.0, 0.5),
         }
     }
 
@@ -55,48 +51,38 @@ impl SawSynth {
 
 impl SynthEngine for SawSynth {
     fn note_on(&mut self, note: u8, _velocity: f32) {
-        let slot = self.voices.iter().position(|v| !v.active).unwrap_or(0);
+        let slot = self.voices.iter().position(|v| !v.envelope.is_active()).unwrap_or(0);
         self.voices[slot] = SawVoice {
             phase: 0.0,
             freq: Self::midi_to_freq(note),
-            active: true,
-            envelope: 0.0,
+            envelope: Envelope::triggered(),
             note,
-            releasing: false,
             filter_state: 0.0,
         };
     }
 
     fn note_off(&mut self, note: u8) {
         for v in &mut self.voices {
-            if v.active && v.note == note {
-                v.releasing = true;
+            if v.envelope.is_active() && v.note == note {
+                v.envelope.release();
             }
         }
     }
 
     fn process(&mut self, output: &mut [f32]) {
         let sr = SAMPLE_RATE as f64;
-        let attack_rate = 1.0 / (self.attack * SAMPLE_RATE as f32).max(1.0);
-        let decay_rate = 1.0 / (self.decay * SAMPLE_RATE as f32).max(1.0);
-        let filter_coeff = (self.cutoff * self.cutoff).clamp(0.001, 0.999);
 
         for sample in output.iter_mut() {
+            let cutoff = self.cutoff.tick();
+            let filter_coeff = (cutoff * cutoff).clamp(0.001, 0.999);
+            let resonance = self.resonance.tick();
             let mut sum = 0.0f32;
             for voice in &mut self.voices {
-                if !voice.active {
+                if !voice.envelope.is_active() {
                     continue;
                 }
 
-                if voice.releasing {
-                    voice.envelope -= decay_rate;
-                    if voice.envelope <= 0.0 {
-                        voice.active = false;
-                        continue;
-                    }
-                } else if voice.envelope < 1.0 {
-                    voice.envelope = (voice.envelope + attack_rate).min(1.0);
-                }
+                let env = voice.envelope.next(&self.adsr);
 
                 // Bandlimited saw: polyBLEP approximation
                 let t = voice.phase as f32;
@@ -113,9 +99,9 @@ impl SynthEngine for SawSynth {
 
                 // Simple lowpass filter
                 voice.filter_state += filter_coeff * (raw - voice.filter_state);
-                let filtered = voice.filter_state + self.resonance * (voice.filter_state - raw);
+                let filtered = voice.filter_state + resonance * (voice.filter_state - raw);
 
-                sum += filtered * voice.envelope * 0.25;
+                sum += filtered * env * 0.25;
 
                 voice.phase += voice.freq / sr;
                 if voice.phase >= 1.0 {
@@ -128,22 +114,26 @@ impl SynthEngine for SawSynth {
 
     fn set_param(&mut self, index: usize, value: f32) {
         match index {
-            0 => self.cutoff = value.clamp(0.01, 1.0),
-            1 => self.resonance = value.clamp(0.0, 0.95),
-            2 => self.attack = value.clamp(0.001, 2.0),
-            3 => self.decay = value.clamp(0.01, 5.0),
-            _ => {}
+            0 => self.cutoff.set(value.clamp(0.01, 1.0)),
+            1 => self.resonance.set(value.clamp(0.0, 0.95)),
+            _ => self.adsr.set_param(index - 2, value),
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.cutoff.target(),
+            1 => self.resonance.target(),
+            _ => self.adsr.get_param(index - 2),
         }
     }
 
-    fn param_count(&self) -> usize { 4 }
+    fn param_count(&self) -> usize { 2 + ENV_PARAM_COUNT }
     fn param_name(&self, index: usize) -> &str {
         match index {
             0 => "CUTOFF",
             1 => "RESO",
-            2 => "ATTACK",
-            3 => "DECAY",
-            _ => "",
+            _ => envelope::param_name(index - 2),
         }
     }
     fn name(&self) -> &str { "SAW" }
@@ -1,4 +1,6 @@
+use crate::audio::smoothing::SmoothedParam;
 use crate::constants::SAMPLE_RATE;
+use crate::synth::envelope::{self, Adsr, Envelope, ENV_PARAM_COUNT};
 use crate::synth::SynthEngine;
 
 const MAX_VOICES: usize = 8;
@@ -6,45 +8,29 @@ const MAX_VOICES: usize = 8;
 /// Karplus-Strong plucked string synthesis
 pub struct StringSynth {
     voices: [StringVoice; MAX_VOICES],
+    /// Level of the pluck's noise burst; only read at note on
     brightness: f32,
-    damping: f32,
-    attack: f32,
-    decay: f32,
+    /// DAMP setting, 0-1; the loop gain runs from 0.99 to 0.999
+    damping: SmoothedParam,
+    adsr: Adsr,
 }
 
+#[derive(Clone)]
 struct StringVoice {
     delay_line: Vec<f32>,
     write_pos: usize,
-    active: bool,
     note: u8,
-    envelope: f32,
-    releasing: bool,
+    envelope: Envelope,
     prev_sample: f32,
 }
 
-impl Clone for StringVoice {
-    fn clone(&self) -> Self {
-        Self {
-            delay_line: self.delay_line.clone(),
-            write_pos: self.write_pos,
-            active: self.active,
-            note: self.note,
-            envelope: self.envelope,
-            releasing: self.releasing,
-            prev_sample: self.prev_sample,
-        }
-    }
-}
-
 impl Default for StringVoice {
     fn default() -> Self {
         Self {
             delay_line: vec![0.0; 1024],
             write_pos: 0,
-            active: false,
             note: 0,
-            envelope: 0.0,
-            releasing: false,
+            envelope: Envelope::default(),
             prev_sample: 0.0,
         }
     }
@@ -55,9 +41,8 @@ impl StringSynth {
         Self {
             voices: std::array::from_fn(|_| StringVoice::default()),
             brightness: 0.5,
-            damping: 0.996,
-            attack: 0.001,
-            decay: 2.0,
+            damping: SmoothedParam::new(2.0 / 3.0), // loop gain 0.996
+            adsr: Adsr::new(0.001, 2.0, 1.0, 2.0),
         }
     }
 
@@ -68,10 +53,10 @@ impl StringSynth {
 
 impl SynthEngine for StringSynth {
     fn note_on(&mut self, note: u8, _velocity: f32) {
-        let slot = self.voices.iter().position(|v| !v.active).unwrap_or(0);
+        let slot = self.voices.iter().position(|v| !v.envelope.is_active()).unwrap_or(0);
         let freq = Self::midi_to_freq(note);
         let delay_len = (SAMPLE_RATE as f64 / freq) as usize;
-        let delay_len = delay_len.max(2).min(4096);
+        let delay_len = delay_len.clamp(2, 4096);
 
         // Initialize delay line with noise burst (the "pluck")
         let mut delay_line = vec![0.0f32; delay_len];
@@ -85,47 +70,37 @@ impl SynthEngine for StringSynth {
         self.voices[slot] = StringVoice {
             delay_line,
             write_pos: 0,
-            active: true,
             note,
-            envelope: 1.0,
-            releasing: false,
+            envelope: Envelope::triggered(),
             prev_sample: 0.0,
         };
     }
 
     fn note_off(&mut self, note: u8) {
         for v in &mut self.voices {
-            if v.active && v.note == note {
-                v.releasing = true;
+            if v.envelope.is_active() && v.note == note {
+                v.envelope.release();
             }
         }
     }
 
     fn process(&mut self, output: &mut [f32]) {
-        let damping = self.damping;
-        let decay_rate = 1.0 / (self.decay * SAMPLE_RATE as f32).max(1.0);
-
         for sample in output.iter_mut() {
+            let damping = 0.99 + self.damping.tick() * 0.009;
             let mut sum = 0.0f32;
             for voice in &mut self.voices {
-                if !voice.active {
+                if !voice.envelope.is_active() {
                     continue;
                 }
 
-                if voice.releasing {
-                    voice.envelope -= decay_rate;
-                    if voice.envelope <= 0.0 {
-                        voice.active = false;
-                        continue;
-                    }
-                }
-
                 let len = voice.delay_line.len();
                 if len < 2 {
-                    voice.active = false;
+                    voice.envelope = Envelope::default();
                     continue;
                 }
 
+                let env = voice.envelope.next(&self.adsr);
+
                 // Read from delay line
                 let read_pos = voice.write_pos;
                 let current = voice.delay_line[read_pos];
@@ -138,7 +113,7 @@ impl SynthEngine for StringSynth {
                 voice.delay_line[voice.write_pos] = filtered;
                 voice.write_pos = (voice.write_pos + 1) % len;
 
-                sum += filtered * voice.envelope * 0.4;
+                sum += filtered * env * 0.4;
             }
             *sample += sum;
         }
@@ -147,21 +122,25 @@ impl SynthEngine for StringSynth {
     fn set_param(&mut self, index: usize, value: f32) {
         match index {
             0 => self.brightness = value.clamp(0.1, 1.0),
-            1 => self.damping = 0.99 + value * 0.009, // 0.99 to 0.999
-            2 => self.attack = value.clamp(0.001, 0.1),
-            3 => self.decay = value.clamp(0.1, 10.0),
-            _ => {}
+            1 => self.damping.set(value.clamp(0.0, 1.0)),
+            _ => self.adsr.set_param(index - 2, value),
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.brightness,
+            1 => self.damping.target(),
+            _ => self.adsr.get_param(index - 2),
         }
     }
 
-    fn param_count(&self) -> usize { 4 }
+    fn param_count(&self) -> usize { 2 + ENV_PARAM_COUNT }
     fn param_name(&self, index: usize) -> &str {
         match index {
             0 => "BRIGHT",
             1 => "DAMP",
-            2 => "ATTACK",
-            3 => "DECAY",
-            _ => "",
+            _ => envelope::param_name(index - 2),
         }
     }
     fn name(&self) -> &str { "STRING" }
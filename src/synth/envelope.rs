@@ -0,0 +1,323 @@
+use crate::constants::SAMPLE_RATE;
+
+/// Number of envelope parameters every engine exposes after its own params
+pub const ENV_PARAM_COUNT: usize = 5;
+
+/// Level below which an exponential release is considered finished (-80 dB)
+const SILENCE: f32 = 0.0001;
+/// Exponential attack aims past full scale so it reaches 1.0 in finite time
+const ATTACK_OVERSHOOT: f32 = 1.3;
+/// Shortest and longest attack, and decay/release, in seconds
+const ATTACK_RANGE: (f32, f32) = (0.001, 2.0);
+const FALL_RANGE: (f32, f32) = (0.01, 5.0);
+
+/// Time range of an envelope parameter, for the time stages only
+fn time_range(index: usize) -> Option<(f32, f32)> {
+    match index {
+        0 => Some(ATTACK_RANGE),
+        1 | 3 => Some(FALL_RANGE),
+        _ => None,
+    }
+}
+
+/// Seconds for a normalized time parameter; the curve is exponential so the
+/// short times that matter most get most of the knob
+pub fn param_to_seconds(index: usize, value: f32) -> f32 {
+    match time_range(index) {
+        Some((min, max)) => min * (max / min).powf(value.clamp(0.0, 1.0)),
+        None => value,
+    }
+}
+
+/// Normalized parameter value for a time in seconds
+pub fn seconds_to_param(index: usize, seconds: f32) -> f32 {
+    match time_range(index) {
+        Some((min, max)) => ((seconds.clamp(min, max) / min).ln() / (max / min).ln()).clamp(0.0, 1.0),
+        None => seconds,
+    }
+}
+
+pub fn param_name(index: usize) -> &'static str {
+    match index {
+        0 => "ATTACK",
+        1 => "DECAY",
+        2 => "SUSTAIN",
+        3 => "RELEASE",
+        4 => "CURVE",
+        _ => "",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeCurve {
+    Linear,
+    Exponential,
+}
+
+/// Shared ADSR settings for all voices of an engine. Parameters use the
+/// 0.0-1.0 range of `SynthEngine::set_param`; times map through `param_to_seconds`.
+#[derive(Clone, Copy)]
+pub struct Adsr {
+    attack: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+    curve: EnvelopeCurve,
+    // Per-sample increments (linear) or one-pole coefficients (exponential)
+    attack_rate: f32,
+    decay_rate: f32,
+    release_rate: f32,
+}
+
+impl Adsr {
+    /// Envelope with times given in seconds
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        let mut adsr = Self {
+            attack: seconds_to_param(0, attack),
+            decay: seconds_to_param(1, decay),
+            sustain: sustain.clamp(0.0, 1.0),
+            release: seconds_to_param(3, release),
+            curve: EnvelopeCurve::Linear,
+            attack_rate: 0.0,
+            decay_rate: 0.0,
+            release_rate: 0.0,
+        };
+        adsr.update_rates();
+        adsr
+    }
+
+    /// Set an envelope parameter; `index` is relative to the envelope block
+    pub fn set_param(&mut self, index: usize, value: f32) {
+        match index {
+            0 => self.attack = value.clamp(0.0, 1.0),
+            1 => self.decay = value.clamp(0.0, 1.0),
+            2 => self.sustain = value.clamp(0.0, 1.0),
+            3 => self.release = value.clamp(0.0, 1.0),
+            4 => {
+                self.curve = if value < 0.5 {
+                    EnvelopeCurve::Linear
+                } else {
+                    EnvelopeCurve::Exponential
+                };
+            }
+            _ => return,
+        }
+        self.update_rates();
+    }
+
+    pub fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.attack,
+            1 => self.decay,
+            2 => self.sustain,
+            3 => self.release,
+            4 => match self.curve {
+                EnvelopeCurve::Linear => 0.0,
+                EnvelopeCurve::Exponential => 1.0,
+            },
+            _ => 0.0,
+        }
+    }
+
+    fn update_rates(&mut self) {
+        let sr = SAMPLE_RATE as f32;
+        let attack = param_to_seconds(0, self.attack) * sr;
+        let decay = param_to_seconds(1, self.decay) * sr;
+        let release = param_to_seconds(3, self.release) * sr;
+        match self.curve {
+            EnvelopeCurve::Linear => {
+                self.attack_rate = 1.0 / attack.max(1.0);
+                self.decay_rate = (1.0 - self.sustain) / decay.max(1.0);
+                self.release_rate = 1.0 / release.max(1.0);
+            }
+            EnvelopeCurve::Exponential => {
+                // Attack reaches 1.0 (of the overshoot target) after the attack time
+                let attack_tau = (ATTACK_OVERSHOOT / (ATTACK_OVERSHOOT - 1.0)).ln();
+                self.attack_rate = 1.0 - (-attack_tau / attack.max(1.0)).exp();
+                // Decay and release fall by 60 dB over their time
+                let db60 = 1000.0f32.ln();
+                self.decay_rate = 1.0 - (-db60 / decay.max(1.0)).exp();
+                self.release_rate = 1.0 - (-db60 / release.max(1.0)).exp();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Per-voice envelope state driven by a shared [`Adsr`]
+#[derive(Clone, Copy)]
+pub struct Envelope {
+    stage: Stage,
+    level: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            stage: Stage::Idle,
+            level: 0.0,
+        }
+    }
+}
+
+impl Envelope {
+    /// A fresh envelope already in its attack stage
+    pub fn triggered() -> Self {
+        Self {
+            stage: Stage::Attack,
+            level: 0.0,
+        }
+    }
+
+    /// Start the attack from the current level so retriggers don't click
+    pub fn trigger(&mut self) {
+        self.stage = Stage::Attack;
+    }
+
+    pub fn release(&mut self) {
+        if self.stage != Stage::Idle {
+            self.stage = Stage::Release;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.stage != Stage::Idle
+    }
+
+    pub fn is_releasing(&self) -> bool {
+        self.stage == Stage::Release
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Advance one sample and return the new level
+    pub fn next(&mut self, adsr: &Adsr) -> f32 {
+        let exp = adsr.curve == EnvelopeCurve::Exponential;
+        match self.stage {
+            Stage::Idle => {
+                self.level = 0.0;
+            }
+            Stage::Attack => {
+                if exp {
+                    self.level += (ATTACK_OVERSHOOT - self.level) * adsr.attack_rate;
+                } else {
+                    self.level += adsr.attack_rate;
+                }
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                if exp {
+                    self.level += (adsr.sustain - self.level) * adsr.decay_rate;
+                } else {
+                    self.level -= adsr.decay_rate;
+                }
+                if self.level <= adsr.sustain + SILENCE {
+                    self.level = adsr.sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => {
+                // Follow sustain changes made while the note is held
+                self.level = adsr.sustain;
+                if self.level <= 0.0 {
+                    self.stage = Stage::Idle;
+                }
+            }
+            Stage::Release => {
+                if exp {
+                    self.level -= self.level * adsr.release_rate;
+                } else {
+                    self.level -= adsr.release_rate;
+                }
+                if self.level <= SILENCE {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(env: &mut Envelope, adsr: &Adsr, samples: usize) -> f32 {
+        for _ in 0..samples {
+            env.next(adsr);
+        }
+        env.level()
+    }
+
+    #[test]
+    fn linear_envelope_settles_at_sustain() {
+        let adsr = Adsr::new(0.01, 0.01, 0.5, 0.01);
+        let mut env = Envelope::triggered();
+        let level = run(&mut env, &adsr, SAMPLE_RATE as usize / 10);
+        assert!((level - 0.5).abs() < 1e-6);
+        assert!(env.is_active());
+    }
+
+    #[test]
+    fn release_ends_the_voice() {
+        for curve in [0.0, 1.0] {
+            let mut adsr = Adsr::new(0.001, 0.01, 0.8, 0.05);
+            adsr.set_param(4, curve);
+            let mut env = Envelope::triggered();
+            run(&mut env, &adsr, 1000);
+            env.release();
+            assert!(env.is_releasing());
+            run(&mut env, &adsr, SAMPLE_RATE as usize);
+            assert!(!env.is_active());
+            assert_eq!(env.level(), 0.0);
+        }
+    }
+
+    #[test]
+    fn exponential_attack_reaches_full_scale_in_time() {
+        let mut adsr = Adsr::new(0.01, 0.1, 1.0, 0.1);
+        adsr.set_param(4, 1.0);
+        let mut env = Envelope::triggered();
+        let level = run(&mut env, &adsr, (0.011 * SAMPLE_RATE as f32) as usize);
+        assert_eq!(level, 1.0);
+    }
+
+    #[test]
+    fn zero_sustain_goes_idle_after_decay() {
+        let adsr = Adsr::new(0.001, 0.01, 0.0, 0.1);
+        let mut env = Envelope::triggered();
+        run(&mut env, &adsr, SAMPLE_RATE as usize / 10);
+        assert!(!env.is_active());
+    }
+
+    #[test]
+    fn params_round_trip_in_the_normalized_range() {
+        let mut adsr = Adsr::new(0.01, 0.3, 1.0, 0.3);
+        for index in 0..4 {
+            for step in 0..=100 {
+                let x = step as f32 / 100.0;
+                adsr.set_param(index, x);
+                assert_eq!(adsr.get_param(index), x);
+            }
+        }
+        for curve in [0.0, 1.0] {
+            adsr.set_param(4, curve);
+            assert_eq!(adsr.get_param(4), curve);
+        }
+        assert!((param_to_seconds(3, seconds_to_param(3, 2.0)) - 2.0).abs() < 1e-4);
+    }
+}
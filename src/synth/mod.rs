@@ -1,13 +1,29 @@
 pub mod voice;
+pub mod envelope;
+pub mod patch;
+pub mod arpeggiator;
 pub mod engines;
 
+use std::sync::Arc;
+
 /// Trait for a synthesizer engine
 pub trait SynthEngine: Send {
     fn note_on(&mut self, note: u8, velocity: f32);
     fn note_off(&mut self, note: u8);
     fn process(&mut self, output: &mut [f32]);
     fn set_param(&mut self, index: usize, value: f32);
+    /// Current value of a parameter, in the same 0.0-1.0 range `set_param` takes
+    fn get_param(&self, index: usize) -> f32;
     fn param_count(&self) -> usize;
     fn param_name(&self, index: usize) -> &str;
     fn name(&self) -> &str;
+    /// Hand the engine sample data to play, returning the sample it replaces;
+    /// engines that don't use samples ignore it
+    fn load_sample(&mut self, _samples: Arc<[f32]>) -> Option<Arc<[f32]>> { None }
+}
+
+impl std::fmt::Debug for dyn SynthEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SynthEngine").field("name", &self.name()).finish()
+    }
 }
@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::synth::engines::ENGINE_NAMES;
+use crate::synth::envelope::{self, ENV_PARAM_COUNT};
+
+/// A synth patch: engine, its own parameters and the envelope.
+/// Values use the same 0.0-1.0 range as `SynthEngine::set_param`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Patch {
+    pub name: String,
+    /// Engine name as listed in `ENGINE_NAMES`
+    pub engine: String,
+    /// Engine-specific parameters, in `param_name` order
+    pub params: Vec<f32>,
+    pub envelope: EnvelopePatch,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct EnvelopePatch {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    pub exponential: bool,
+}
+
+impl Patch {
+    /// Build a patch from a full engine parameter list (envelope last)
+    pub fn from_values(name: &str, engine: usize, values: &[f32]) -> Self {
+        let split = values.len().saturating_sub(ENV_PARAM_COUNT);
+        let env = |i: usize| values.get(split + i).copied().unwrap_or(0.0);
+        Self {
+            name: name.to_string(),
+            engine: ENGINE_NAMES.get(engine).unwrap_or(&ENGINE_NAMES[0]).to_string(),
+            params: values[..split].to_vec(),
+            envelope: EnvelopePatch {
+                attack: env(0),
+                decay: env(1),
+                sustain: env(2),
+                release: env(3),
+                exponential: env(4) >= 0.5,
+            },
+        }
+    }
+
+    pub fn engine_index(&self) -> Option<usize> {
+        crate::synth::engines::engine_index(&self.engine)
+    }
+
+    /// Lay the patch over an engine's default values (envelope last), so a
+    /// patch with missing or extra engine params still lines up correctly
+    pub fn values(&self, defaults: &[f32]) -> Vec<f32> {
+        let mut values = defaults.to_vec();
+        let split = values.len().saturating_sub(ENV_PARAM_COUNT);
+        for (value, param) in values[..split].iter_mut().zip(&self.params) {
+            *value = param.clamp(0.0, 1.0);
+        }
+        let env = &self.envelope;
+        let curve = if env.exponential { 1.0 } else { 0.0 };
+        let env_values = [env.attack, env.decay, env.sustain, env.release, curve];
+        for (value, param) in values[split..].iter_mut().zip(env_values) {
+            *value = param.clamp(0.0, 1.0);
+        }
+        values
+    }
+}
+
+/// User preset directory: `$XDG_CONFIG_HOME/tapedeck/presets` or `~/.config/tapedeck/presets`
+pub fn presets_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(base.join("tapedeck").join("presets"))
+}
+
+/// Load every `*.json` patch in `dir`, skipping files that don't parse
+pub fn load_user_presets(dir: &Path) -> Vec<Patch> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|p| load_patch(p).ok())
+        .filter(|p| p.engine_index().is_some())
+        .collect()
+}
+
+pub fn load_patch(path: &Path) -> Result<Patch, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Write a patch as `<dir>/<name>.json`, returning the file path
+pub fn save_patch(dir: &Path, patch: &Patch) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let file_name: String = patch
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}.json", file_name));
+    std::fs::write(&path, serde_json::to_string_pretty(patch)?)?;
+    Ok(path)
+}
+
+/// Built-in presets, a few per engine
+pub fn factory_presets() -> Vec<Patch> {
+    // Envelope times in seconds
+    fn patch(name: &str, engine: &str, params: &[f32], adsr: [f32; 4], exponential: bool) -> Patch {
+        Patch {
+            name: name.to_string(),
+            engine: engine.to_string(),
+            params: params.to_vec(),
+            envelope: EnvelopePatch {
+                attack: envelope::seconds_to_param(0, adsr[0]),
+                decay: envelope::seconds_to_param(1, adsr[1]),
+                sustain: adsr[2],
+                release: envelope::seconds_to_param(3, adsr[3]),
+                exponential,
+            },
+        }
+    }
+
+    vec![
+        patch("Soft Keys", "SINE", &[0.5], [0.01, 0.4, 0.6, 0.4], false),
+        patch("Sub Pad", "SINE", &[0.48], [0.6, 1.0, 1.0, 1.0], true),
+        patch("Acid Bass", "SAW", &[0.35, 0.8], [0.001, 0.2, 0.3, 0.1], true),
+        patch("Warm Lead", "SAW", &[0.6, 0.3], [0.02, 0.3, 0.8, 0.3], false),
+        patch("E.Piano", "FM", &[0.125, 0.3], [0.001, 0.8, 0.2, 0.5], true),
+        patch("Bell", "FM", &[0.5, 0.6], [0.001, 1.0, 0.0, 1.0], true),
+        patch("Nylon", "STRING", &[0.6, 0.6], [0.001, 1.0, 1.0, 0.8], false),
+        patch("Harp", "STRING", &[0.9, 0.9], [0.001, 1.0, 1.0, 1.0], true),
+        patch("Hat", "NOISE", &[0.9, 0.2], [0.001, 0.05, 0.0, 0.05], true),
+        patch("Wind", "NOISE", &[0.3, 0.8], [0.8, 1.0, 1.0, 1.0], false),
+    ]
+}
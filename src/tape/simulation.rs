@@ -1,8 +1,13 @@
+use crate::audio::smoothing::SmoothedParam;
 use crate::constants::SAMPLE_RATE;
+use crate::effects::tempo::TempoInfo;
+use crate::effects::{self, Effect};
 
 /// Tape simulation processor: wow, flutter, saturation, hiss, HF rolloff
 pub struct TapeSimulation {
     pub enabled: bool,
+    /// Whether the tape is moving; like the real thing, it only colours the sound then
+    running: bool,
     // Wow: slow pitch modulation
     wow_phase: f64,
     pub wow_depth: f32,
@@ -12,12 +17,12 @@ pub struct TapeSimulation {
     pub flutter_depth: f32,
     pub flutter_rate: f32,
     // Saturation
-    pub drive: f32,
+    drive: SmoothedParam,
     // Hiss
     noise_state: u32,
-    pub hiss_level: f32,
+    hiss_level: SmoothedParam,
     // HF rolloff (one-pole lowpass)
-    pub rolloff_freq: f32,
+    rolloff_freq: SmoothedParam,
     lp_state_l: f32,
     lp_state_r: f32,
 }
@@ -26,16 +31,17 @@ impl TapeSimulation {
     pub fn new() -> Self {
         Self {
             enabled: false,
+            running: true,
             wow_phase: 0.0,
             wow_depth: 0.002,
             wow_rate: 1.0,
             flutter_phase: 0.0,
             flutter_depth: 0.0003,
             flutter_rate: 8.0,
-            drive: 2.0,
+            drive: SmoothedParam::new(2.0),
             noise_state: 42,
-            hiss_level: 0.005,
-            rolloff_freq: 14000.0,
+            hiss_level: SmoothedParam::new(0.005),
+            rolloff_freq: SmoothedParam::new(14000.0),
             lp_state_l: 0.0,
             lp_state_r: 0.0,
         }
@@ -48,12 +54,13 @@ impl TapeSimulation {
         }
 
         // Saturation: tanh waveshaping
-        *left = (*left * self.drive).tanh() / self.drive.tanh();
-        *right = (*right * self.drive).tanh() / self.drive.tanh();
+        let drive = self.drive.tick();
+        *left = (*left * drive).tanh() / drive.tanh();
+        *right = (*right * drive).tanh() / drive.tanh();
 
         // HF rolloff (simple one-pole lowpass)
         let sr = SAMPLE_RATE as f32;
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * self.rolloff_freq);
+        let rc = 1.0 / (2.0 * std::f32::consts::PI * self.rolloff_freq.tick());
         let dt = 1.0 / sr;
         let alpha = dt / (rc + dt);
 
@@ -65,8 +72,9 @@ impl TapeSimulation {
         // Hiss (pink-ish noise)
         self.noise_state = self.noise_state.wrapping_mul(1664525).wrapping_add(1013904223);
         let noise = (self.noise_state as f32 / u32::MAX as f32) * 2.0 - 1.0;
-        *left += noise * self.hiss_level;
-        *right += noise * self.hiss_level * 0.8; // Slightly different per channel
+        let hiss = self.hiss_level.tick();
+        *left += noise * hiss;
+        *right += noise * hiss * 0.8; // Slightly different per channel
     }
 
     /// Get wow+flutter pitch offset in fractional samples
@@ -86,3 +94,53 @@ impl TapeSimulation {
         wow + flutter
     }
 }
+
+/// Parameter ranges, in `param_name` order; `set_param` clamps to these
+const PARAM_RANGES: [(f32, f32); 3] = [(1.0, 10.0), (0.0, 0.05), (2000.0, 20000.0)];
+
+/// Tape simulation as a master bus slot; bypassing it disables it
+impl Effect for TapeSimulation {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if !self.running {
+            return;
+        }
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            self.process_stereo(l, r);
+        }
+    }
+
+    fn set_param(&mut self, index: usize, value: f32) {
+        let value = effects::clamp_param(&PARAM_RANGES, index, value);
+        match index {
+            0 => self.drive.set(value),
+            1 => self.hiss_level.set(value),
+            2 => self.rolloff_freq.set(value),
+            _ => {}
+        }
+    }
+
+    fn get_param(&self, index: usize) -> f32 {
+        match index {
+            0 => self.drive.target(),
+            1 => self.hiss_level.target(),
+            2 => self.rolloff_freq.target(),
+            _ => 0.0,
+        }
+    }
+
+    fn param_range(&self, index: usize) -> (f32, f32) { effects::param_range(&PARAM_RANGES, index) }
+
+    fn param_count(&self) -> usize { PARAM_RANGES.len() }
+    fn param_name(&self, index: usize) -> &str {
+        match index { 0 => "DRIVE", 1 => "HISS", 2 => "TONE", _ => "" }
+    }
+    fn name(&self) -> &str { "TAPE" }
+    fn set_bypass(&mut self, bypass: bool) { self.enabled = !bypass; }
+    fn is_bypassed(&self) -> bool { !self.enabled }
+    fn set_transport(&mut self, tempo: &TempoInfo) { self.running = tempo.playing; }
+    fn settle(&mut self) {
+        for param in [&mut self.drive, &mut self.hiss_level, &mut self.rolloff_freq] {
+            param.settle();
+        }
+    }
+}
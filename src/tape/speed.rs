@@ -24,31 +24,36 @@ impl SpeedControl {
 
     /// Read a sample with cubic interpolation from a buffer
     pub fn read_interpolated(&self, buffer: &[f32], position: f64) -> f32 {
-        let len = buffer.len();
-        if len < 4 {
-            return 0.0;
-        }
+        cubic_interpolate(buffer, position)
+    }
+}
+
+/// Read `buffer` at a fractional position with cubic Hermite interpolation
+pub fn cubic_interpolate(buffer: &[f32], position: f64) -> f32 {
+    let len = buffer.len();
+    if len < 4 {
+        return 0.0;
+    }
 
-        let pos = position.max(0.0);
-        let idx = pos as usize;
-        let frac = pos - idx as f64;
+    let pos = position.max(0.0);
+    let idx = pos as usize;
+    let frac = pos - idx as f64;
 
-        if idx + 2 >= len {
-            return if idx < len { buffer[idx] } else { 0.0 };
-        }
+    if idx + 2 >= len {
+        return if idx < len { buffer[idx] } else { 0.0 };
+    }
 
-        let y0 = if idx > 0 { buffer[idx - 1] } else { buffer[0] };
-        let y1 = buffer[idx];
-        let y2 = buffer[idx + 1];
-        let y3 = buffer[(idx + 2).min(len - 1)];
+    let y0 = if idx > 0 { buffer[idx - 1] } else { buffer[0] };
+    let y1 = buffer[idx];
+    let y2 = buffer[idx + 1];
+    let y3 = buffer[(idx + 2).min(len - 1)];
 
-        // Cubic Hermite interpolation
-        let frac = frac as f32;
-        let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
-        let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
-        let c = -0.5 * y0 + 0.5 * y2;
-        let d = y1;
+    // Cubic Hermite interpolation
+    let frac = frac as f32;
+    let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+    let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c = -0.5 * y0 + 0.5 * y2;
+    let d = y1;
 
-        a * frac * frac * frac + b * frac * frac + c * frac + d
-    }
+    a * frac * frac * frac + b * frac * frac + c * frac + d
 }
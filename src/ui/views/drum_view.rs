@@ -4,6 +4,8 @@ use ratatui::style::Style;
 use ratatui::widgets::Paragraph;
 
 use crate::app::AppState;
+use crate::sequencer::drum_kit::{self, DRUM_PARAM_NAMES, LANE_NAMES};
+use crate::sequencer::pattern::pattern_label;
 use crate::ui::theme;
 use crate::ui::views::View;
 use crate::ui::widgets::step_grid::StepGridWidget;
@@ -15,38 +17,159 @@ impl View for DrumView {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(2), // BPM + info
+                Constraint::Length(3), // BPM + info
                 Constraint::Min(8),   // Step grid
-                Constraint::Length(2), // Controls hint
+                Constraint::Length(2), // Voice parameters
+                Constraint::Length(5), // Controls hint
             ])
             .split(area);
 
         // BPM display
-        let bpm_str = format!(
-            "  BPM: {:.0}  │  Step: {:2}/16  │  Inst: {}",
+        let pattern = state.drum_pattern();
+        let steps_per_beat = pattern.resolution.steps_per_beat();
+        let mut bpm_str = format!(
+            "  BPM: {:.0} {}  │  Step: {:2}/{} {}  │  Page: {}/{}  │  Inst: {}  │  Kit: {}",
             state.bpm,
-            state.current_step + 1,
-            ["KICK", "SNARE", "HAT", "CLAP", "TOM", "RIM"][state.selected_instrument],
+            state.time_signature.label(),
+            state.current_step % pattern.length + 1,
+            pattern.length,
+            pattern.resolution.label(),
+            state.step_page + 1,
+            state.step_pages(),
+            LANE_NAMES[state.selected_instrument],
+            state.drum_kit_name,
         );
+        if state.drum_perform {
+            bpm_str.push_str(&format!("  │  PERFORM  Quantize: {:.0}%", state.quantize * 100.0));
+        }
         frame.render_widget(
             Paragraph::new(bpm_str).style(Style::default().fg(theme::ACCENT)),
-            chunks[0],
+            Rect::new(chunks[0].x, chunks[0].y, chunks[0].width, 1),
+        );
+
+        // Pattern and song chain
+        let mut pattern_str = format!(
+            "  Pattern: {}  │  Playing: {}",
+            pattern_label(state.selected_pattern),
+            pattern_label(state.playing_pattern),
+        );
+        if let Some(queued) = state.queued_pattern {
+            pattern_str.push_str(&format!(" → {}", pattern_label(queued)));
+        }
+        pattern_str.push_str(if state.song_mode { "  │  SONG:" } else { "  │  Song:" });
+        if state.song.is_empty() {
+            pattern_str.push_str(" —");
+        } else {
+            for (index, pattern) in state.song.iter().enumerate() {
+                let label = pattern_label(*pattern);
+                if state.song_mode && index == state.song_entry {
+                    pattern_str.push_str(&format!(" [{}]", label));
+                } else {
+                    pattern_str.push_str(&format!(" {}", label));
+                }
+            }
+        }
+        let pattern_color = if state.song_mode { theme::PLAYING_GREEN } else { theme::FG };
+        frame.render_widget(
+            Paragraph::new(pattern_str).style(Style::default().fg(pattern_color)),
+            Rect::new(chunks[0].x, chunks[0].y + 1, chunks[0].width, 1),
+        );
+
+        // Groove and the selected step
+        let step = pattern.lanes[state.selected_instrument]
+            .step(state.selected_step)
+            .copied()
+            .unwrap_or_default();
+        let step_str = format!(
+            "  Swing: {:.0}%  Human: {:.0}%  │  Step {}: Vel {:.0}%  Prob {:.0}%  x{}  Nudge {:+.0}%{}",
+            state.swing * 100.0,
+            state.humanize * 100.0,
+            state.selected_step + 1,
+            step.velocity * 100.0,
+            step.probability * 100.0,
+            step.ratchets,
+            step.nudge * 100.0,
+            if step.has_locks() { "  LOCKED" } else { "" },
+        );
+        frame.render_widget(
+            Paragraph::new(step_str).style(Style::default().fg(theme::FG)),
+            Rect::new(chunks[0].x, chunks[0].y + 2, chunks[0].width, 1),
         );
 
         // Step grid
         let grid = StepGridWidget {
-            patterns: state.drum_patterns,
+            pattern,
             current_step: state.current_step,
+            first_step: state.step_page * AppState::STEP_PAGE_LEN,
+            beat_steps: state.time_signature.steps_per_beat(steps_per_beat),
+            bar_steps: state.time_signature.steps_per_bar(steps_per_beat),
             selected_instrument: state.selected_instrument,
+            selected_step: state.selected_step,
             instrument_names: ["KICK", "SNR ", "HAT ", "CLAP", "TOM ", "RIM "],
+            mutes: state.drum_mutes,
         };
         frame.render_widget(grid, chunks[1]);
 
+        // Voice parameters of the selected instrument, or the selected step's locks
+        let params = &state.drum_params[state.selected_instrument];
+        let mut x = chunks[2].x + 2;
+        if state.lock_mode {
+            let label = format!("LOCK {} ", state.selected_step + 1);
+            let width = label.chars().count() as u16;
+            frame.render_widget(
+                Paragraph::new(label).style(Style::default().fg(theme::MUTE_YELLOW)),
+                Rect::new(x, chunks[2].y, width.min(chunks[2].width), 1),
+            );
+            x += width;
+        }
+        for (index, name) in DRUM_PARAM_NAMES.iter().enumerate() {
+            let lock = if state.lock_mode { step.locks[index] } else { None };
+            let cell = format!(
+                " {} {} ",
+                name,
+                drum_kit::format_param(index, lock.unwrap_or(params[index]))
+            );
+            let fg = if lock.is_some() { theme::MUTE_YELLOW } else { theme::FG };
+            let style = if index == state.selected_drum_param {
+                Style::default().fg(if lock.is_some() { fg } else { theme::ACCENT }).bg(theme::SELECTED_BG)
+            } else {
+                Style::default().fg(fg)
+            };
+            let width = cell.chars().count() as u16;
+            if x + width > chunks[2].x + chunks[2].width {
+                break;
+            }
+            frame.render_widget(
+                Paragraph::new(cell).style(style),
+                Rect::new(x, chunks[2].y, width, 1),
+            );
+            x += width + 1;
+        }
+
+        // The selected lane's sample pad, as set in the kit; TUNE, LEVEL and PAN apply on top
+        if let Some(Some(pad)) = state.drum_pads.get(state.selected_instrument) {
+            let decay = if pad.decay > 0.0 { format!("{:.0}ms", pad.decay) } else { "full".to_string() };
+            let choke = pad.choke.map_or("—".to_string(), |group| group.to_string());
+            let pad_str = format!(
+                "  PAD {}  Pitch {:+.1}st  Decay {}  Gain {:.2}  Choke {}",
+                pad.file, pad.pitch, decay, pad.gain, choke,
+            );
+            frame.render_widget(
+                Paragraph::new(pad_str).style(Style::default().fg(theme::DIM)),
+                Rect::new(chunks[2].x, chunks[2].y + 1, chunks[2].width, 1),
+            );
+        }
+
         // Controls
+        let first_hint = if state.drum_perform {
+            "  Z-N/A-H:Play  `:Edit Steps  ~:Quantize"
+        } else {
+            "  Z-K:Toggle Steps  `:Perform"
+        };
         frame.render_widget(
-            Paragraph::new("  Z-K:Toggle Steps  1-6:Instrument  ↑/↓:BPM  R:Record")
+            Paragraph::new(first_hint.to_string() + "  1-6:Instrument  ↑/↓:BPM  [/]:Param  -/=:Adjust  R:Record  ;:Kit  .:Mute\n  O/P:Pattern  Y/U:Copy/Paste  Bksp:Clear  W:Song Mode  E/T:Song +/-\n  ←/→:Page  {/}:Length  _/+:Lane Length  \\:Resolution  |:Time Sig\n  Shift+←/→:Swing  Shift+↑/↓:Humanize  (/):Nudge Step\n  Shift+Z-K:Select Step  7/8:Velocity  9/0:Probability  /:Ratchets  ':Lock Mode  \":Clear Locks")
                 .style(Style::default().fg(theme::DIM)),
-            chunks[2],
+            chunks[3],
         );
     }
 }
@@ -1,13 +1,16 @@
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::Style;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 
 use crate::app::AppState;
-use crate::constants::TRACK_COUNT;
+use crate::constants::{AUX_COUNT, AUX_NAMES, FX_SLOTS, MASTER_INSERTS, TRACK_COUNT};
+use crate::effects::{self, eq::CHANNEL_EQ_NAMES};
 use crate::ui::layout::MixerLayout;
 use crate::ui::theme;
 use crate::ui::views::View;
+use crate::ui::widgets::knob::KnobWidget;
 use crate::ui::widgets::vu_meter::VuMeterWidget;
 
 pub struct MixerView;
@@ -33,6 +36,9 @@ fn render_channel_strip(state: &AppState, frame: &mut Frame, area: Rect, track:
             Constraint::Length(2), // Track header
             Constraint::Min(4),   // Fader
             Constraint::Length(1), // Pan
+            Constraint::Length(1), // Channel EQ
+            Constraint::Length(FX_SLOTS as u16 + 1), // Insert slots
+            Constraint::Length(6), // Aux sends
             Constraint::Length(1), // VU meter
             Constraint::Length(1), // Mute/Solo
         ])
@@ -91,6 +97,71 @@ fn render_channel_strip(state: &AppState, frame: &mut Frame, area: Rect, track:
         chunks[2],
     );
 
+    // Channel EQ gains; the band being edited is highlighted on the selected strip
+    let td = &state.track_displays[track];
+    let mut eq_spans = vec![Span::raw(" ")];
+    for (band, gain) in td.eq.iter().enumerate() {
+        let style = if is_selected && band == state.selected_eq_band {
+            Style::default().fg(theme::ACCENT).bg(theme::SELECTED_BG)
+        } else if *gain == 0.0 {
+            Style::default().fg(theme::DIM)
+        } else {
+            Style::default().fg(theme::FG)
+        };
+        eq_spans.push(Span::styled(format!("{}{:+.0}", CHANNEL_EQ_NAMES[band], gain), style));
+        eq_spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(eq_spans)), chunks[3]);
+
+    // Insert slots, then the parameters of the slot being edited
+    let mut fx_lines: Vec<Line> = (0..FX_SLOTS)
+        .map(|slot| {
+            let effect = state.effect_types[track][slot];
+            let name = effect.map_or("---", |e| effects::EFFECTS[e].0);
+            let style = if is_selected && slot == state.selected_fx_slot {
+                Style::default().fg(theme::ACCENT).bg(theme::SELECTED_BG)
+            } else if effect.is_none() || state.effect_bypassed[track][slot] {
+                Style::default().fg(theme::DIM)
+            } else {
+                Style::default().fg(theme::FG)
+            };
+            Line::styled(format!(" {} {}", slot + 1, name), style)
+        })
+        .collect();
+    if is_selected {
+        let slot = state.selected_fx_slot;
+        if let Some(effect) = state.effect_types[track][slot] {
+            let values = &state.effect_params[track][slot];
+            let param = state.selected_fx_param.min(values.len().saturating_sub(1));
+            if let (Some(name), Some(value)) = (state.effect_param_names[effect].get(param), values.get(param)) {
+                fx_lines.push(Line::styled(
+                    format!(" {} {}", name, format_param(*value)),
+                    Style::default().fg(theme::ACCENT),
+                ));
+            }
+        }
+    }
+    frame.render_widget(Paragraph::new(fx_lines), chunks[4]);
+
+    // Aux send knobs, labelled PRE when tapped before the fader
+    let send_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, AUX_COUNT as u32); AUX_COUNT])
+        .split(chunks[5]);
+    for aux in 0..AUX_COUNT {
+        let label = if td.send_pre[aux] {
+            format!("{} PRE", AUX_NAMES[aux])
+        } else {
+            AUX_NAMES[aux].to_string()
+        };
+        let knob = KnobWidget {
+            label,
+            value: td.sends[aux],
+            selected: is_selected && aux == state.selected_send,
+        };
+        frame.render_widget(knob, send_areas[aux]);
+    }
+
     // VU
     let meter = VuMeterWidget {
         label: "".to_string(),
@@ -98,16 +169,15 @@ fn render_channel_strip(state: &AppState, frame: &mut Frame, area: Rect, track:
         peak: state.peaks[track],
         color: theme::TRACK_COLORS[track],
     };
-    frame.render_widget(meter, chunks[3]);
+    frame.render_widget(meter, chunks[6]);
 
     // Mute/Solo
-    let td = &state.track_displays[track];
     let mut status = String::from("  ");
     if td.muted {
         status.push_str("M ");
     }
     if td.solo {
-        status.push_str("S");
+        status.push('S');
     }
     let status_color = if td.muted {
         theme::MUTE_YELLOW
@@ -118,17 +188,28 @@ fn render_channel_strip(state: &AppState, frame: &mut Frame, area: Rect, track:
     };
     frame.render_widget(
         Paragraph::new(status).style(Style::default().fg(status_color)),
-        chunks[4],
+        chunks[7],
     );
 }
 
+/// Parameter value with as many decimals as its size calls for
+fn format_param(value: f32) -> String {
+    match value.abs() {
+        v if v >= 100.0 => format!("{:.0}", value),
+        v if v >= 10.0 => format!("{:.1}", value),
+        _ => format!("{:.2}", value),
+    }
+}
+
 fn render_master(state: &AppState, frame: &mut Frame, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(2),
-            Constraint::Min(2),
             Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Min(2),
+            Constraint::Length(1),
         ])
         .split(area);
 
@@ -160,4 +241,45 @@ fn render_master(state: &AppState, frame: &mut Frame, area: Rect) {
         };
         frame.render_widget(meter_r, vu_chunks[1]);
     }
+
+    // Master inserts in processing order; the limiter always comes last
+    frame.render_widget(
+        Paragraph::new("  INSERTS").style(Style::default().fg(theme::DIM)),
+        chunks[2],
+    );
+    let mut lines: Vec<Line> = state
+        .master_chain
+        .iter()
+        .enumerate()
+        .map(|(i, (insert, bypassed))| {
+            let name = MASTER_INSERTS[*insert];
+            let selected = i == state.selected_master_slot;
+            let mut style = Style::default().fg(if *bypassed { theme::DIM } else { theme::FG });
+            if selected {
+                style = style.bg(theme::SELECTED_BG);
+            }
+            let marker = if selected { "▸" } else { " " };
+            let status = if *bypassed { "off" } else { "on" };
+            let mut spans = vec![Span::styled(format!(" {}{} {:<6}{:<4}", marker, i + 1, name, status), style)];
+            // Dynamics inserts show how hard they are working
+            let gr = state.insert_gr.get(i).copied().unwrap_or(0.0);
+            if gr >= 0.1 {
+                spans.push(Span::styled(format!("-{:.1}", gr), Style::default().fg(theme::MUTE_YELLOW)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(Line::from(Span::styled("   LIMIT", Style::default().fg(theme::ACCENT))));
+    frame.render_widget(Paragraph::new(lines), chunks[3]);
+
+    // Limiter gain reduction, one block per dB up to 12
+    let blocks = (state.master_gr.round() as usize).min(12);
+    let gr_color = if state.master_gr > 6.0 { theme::MUTE_YELLOW } else { theme::ACCENT };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(format!(" GR {:>4.1} ", state.master_gr), Style::default().fg(theme::DIM)),
+            Span::styled("█".repeat(blocks), Style::default().fg(gr_color)),
+        ])),
+        chunks[4],
+    );
 }
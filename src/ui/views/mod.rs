@@ -1,5 +1,6 @@
 pub mod tape_view;
 pub mod synth_view;
+pub mod notes_view;
 pub mod drum_view;
 pub mod mixer_view;
 
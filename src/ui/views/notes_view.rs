@@ -0,0 +1,63 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::Paragraph;
+
+use crate::app::AppState;
+use crate::sequencer::note_pattern::{self, STEPS_PER_BAR};
+use crate::synth::engines::ENGINE_NAMES;
+use crate::ui::theme;
+use crate::ui::views::View;
+use crate::ui::widgets::piano_roll::PianoRollWidget;
+
+pub struct NotesView;
+
+impl View for NotesView {
+    fn render(&self, state: &AppState, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2), // Track + bar + note info
+                Constraint::Min(6),    // Piano roll
+            ])
+            .split(area);
+
+        let track = state.selected_track;
+        let pattern = state.note_pattern();
+        let cursor = state.note_cursor.min(pattern.len() - 1);
+        let bar = cursor / STEPS_PER_BAR;
+
+        let note_info = match pattern.get(cursor) {
+            Some(n) => format!(
+                "{}  LEN {}  VEL {:.2}",
+                note_pattern::note_name(n.note),
+                n.length,
+                n.velocity
+            ),
+            None => format!("{}  ---", note_pattern::note_name(state.note_pitch)),
+        };
+        let info = format!(
+            "  T{} {}  │  BAR {}/{}  │  STEP {:2}  │  {}",
+            track + 1,
+            ENGINE_NAMES[state.instrument().engine],
+            bar + 1,
+            pattern.bars(),
+            cursor % STEPS_PER_BAR + 1,
+            note_info,
+        );
+        frame.render_widget(
+            Paragraph::new(info).style(Style::default().fg(theme::TRACK_COLORS[track])),
+            chunks[0],
+        );
+
+        let roll = PianoRollWidget {
+            pattern,
+            first_step: bar * STEPS_PER_BAR,
+            cursor_step: cursor,
+            cursor_pitch: state.note_pitch,
+            playhead: state.seq_step % pattern.len(),
+            color: theme::TRACK_COLORS[track],
+        };
+        frame.render_widget(roll, chunks[1]);
+    }
+}
@@ -4,6 +4,8 @@ use ratatui::style::Style;
 use ratatui::widgets::Paragraph;
 
 use crate::app::AppState;
+use crate::synth::arpeggiator::ARP_RATES;
+use crate::synth::engines::{ENGINE_NAMES, SAMPLER_ENGINE};
 use crate::ui::theme;
 use crate::ui::views::View;
 use crate::ui::widgets::knob::KnobWidget;
@@ -12,22 +14,44 @@ pub struct SynthView;
 
 impl View for SynthView {
     fn render(&self, state: &AppState, frame: &mut Frame, area: Rect) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(40),    // Engine + knobs
+                Constraint::Length(22), // Preset browser
+            ])
+            .split(area);
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3),  // Engine selector
                 Constraint::Min(8),    // Knobs
+                Constraint::Length(1), // Arpeggiator
                 Constraint::Length(3), // Keyboard visualization
             ])
-            .split(area);
+            .split(columns[0]);
+
+        let inst = state.instrument();
 
-        // Engine selector
-        let engines = ["SINE", "SAW", "FM", "STRING", "NOISE"];
-        let engine_str: String = engines
+        // Track this instrument belongs to, then the engine selector
+        let header = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(5), Constraint::Min(0)])
+            .split(chunks[0]);
+
+        let track = state.selected_track;
+        frame.render_widget(
+            Paragraph::new(format!(" T{}", track + 1))
+                .style(Style::default().fg(theme::TRACK_COLORS[track])),
+            header[0],
+        );
+
+        let engine_str: String = ENGINE_NAMES
             .iter()
             .enumerate()
             .map(|(i, name)| {
-                if i == state.synth_engine {
+                if i == inst.engine {
                     format!(" [{}] ", name)
                 } else {
                     format!("  {}  ", name)
@@ -37,36 +61,123 @@ impl View for SynthView {
 
         frame.render_widget(
             Paragraph::new(engine_str).style(Style::default().fg(theme::ACCENT)),
-            chunks[0],
+            header[1],
         );
 
-        // Parameter knobs
-        let knob_area = chunks[1];
-        let knob_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-            ])
-            .split(knob_area);
-
-        let param_names = ["FREQ", "RESO", "ATTACK", "DECAY"];
-        for i in 0..4 {
-            let knob = KnobWidget {
-                label: param_names[i].to_string(),
-                value: state.synth_params[i],
-                selected: i == 0, // TODO: track selected param
-            };
-            frame.render_widget(knob, knob_chunks[i]);
+        // Parameter knobs for the active engine
+        let names = &state.synth_param_names[inst.engine];
+        let values = inst.engine_params();
+        let count = names.len().min(values.len());
+        // Engines with many parameters get a second row of knobs
+        let rows = if count > 7 { 2 } else { 1 };
+        let per_row = count.div_ceil(rows);
+        let row_areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints((0..rows).map(|_| Constraint::Ratio(1, rows as u32)))
+            .split(chunks[1]);
+
+        for (row, row_area) in row_areas.iter().enumerate() {
+            let first = row * per_row;
+            let knobs = per_row.min(count - first);
+            let knob_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints((0..per_row).map(|_| Constraint::Ratio(1, per_row as u32)))
+                .split(*row_area);
+
+            for (i, area) in knob_chunks.iter().take(knobs).enumerate() {
+                let index = first + i;
+                let knob = KnobWidget {
+                    label: names[index].clone(),
+                    value: values[index],
+                    selected: index == state.selected_param,
+                };
+                frame.render_widget(knob, *area);
+            }
         }
 
+        // Arpeggiator settings
+        let arp = &inst.arp;
+        let (arp_str, arp_color) = if arp.enabled {
+            (
+                format!(
+                    "  ARP {}  │  {} OCT  │  {}  │  GATE {:.0}%{}",
+                    arp.mode.label(),
+                    arp.octaves,
+                    ARP_RATES[arp.rate].0,
+                    arp.gate * 100.0,
+                    if arp.latch { "  │  LATCH" } else { "" },
+                ),
+                theme::ACCENT,
+            )
+        } else {
+            ("  ARP OFF  (A:On  F:Mode  1:Oct  8:Rate  9/0:Gate  K:Latch)".to_string(), theme::DIM)
+        };
+        frame.render_widget(
+            Paragraph::new(arp_str).style(Style::default().fg(arp_color)),
+            chunks[2],
+        );
+
         // Keyboard hint
         frame.render_widget(
             Paragraph::new("  Z S X D C V G B H N J M  │  Q 2 W 3 E 4 5 T 6 Y 7 U")
                 .style(Style::default().fg(theme::DIM)),
-            chunks[2],
+            chunks[3],
+        );
+
+        render_preset_browser(state, frame, columns[1]);
+    }
+}
+
+fn render_preset_browser(state: &AppState, frame: &mut Frame, area: Rect) {
+    if area.height < 3 {
+        return;
+    }
+
+    let inst = state.instrument();
+    frame.render_widget(
+        Paragraph::new(format!(" PATCH: {}", inst.patch_name))
+            .style(Style::default().fg(theme::ACCENT)),
+        Rect::new(area.x, area.y, area.width, 1),
+    );
+
+    // Sampler source and loaded sample
+    if inst.engine == SAMPLER_ENGINE {
+        let sample = if inst.sample.is_some() {
+            inst.sample_name.as_str()
+        } else {
+            "EMPTY"
+        };
+        frame.render_widget(
+            Paragraph::new(format!(" T{}> {}", state.sample_source + 1, sample))
+                .style(Style::default().fg(theme::DIM)),
+            Rect::new(area.x, area.y + 1, area.width, 1),
+        );
+    }
+
+    // Keep the cursor in view
+    let rows = (area.height - 2) as usize;
+    let cursor = state.selected_preset.unwrap_or(0);
+    let first = cursor.saturating_sub(rows.saturating_sub(1));
+
+    for (row, (i, patch)) in state
+        .presets
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .enumerate()
+    {
+        let selected = state.selected_preset == Some(i);
+        let marker = if selected { "▶" } else { " " };
+        let tag: String = patch.engine.chars().take(3).collect();
+        let style = if selected {
+            Style::default().fg(theme::ACCENT).bg(theme::SELECTED_BG)
+        } else {
+            Style::default().fg(theme::FG)
+        };
+        frame.render_widget(
+            Paragraph::new(format!("{}{:<3} {}", marker, tag, patch.name)).style(style),
+            Rect::new(area.x, area.y + 2 + row as u16, area.width, 1),
         );
     }
 }
@@ -6,4 +6,5 @@ pub mod waveform;
 pub mod track_selector;
 pub mod knob;
 pub mod step_grid;
+pub mod piano_roll;
 pub mod mode_indicator;
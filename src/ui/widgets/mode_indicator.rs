@@ -16,7 +16,13 @@ impl Widget for ModeIndicatorWidget {
             return;
         }
 
-        let modes = [AppMode::Tape, AppMode::Synth, AppMode::Drum, AppMode::Mixer];
+        let modes = [
+            AppMode::Tape,
+            AppMode::Synth,
+            AppMode::Notes,
+            AppMode::Drum,
+            AppMode::Mixer,
+        ];
         let mut x = area.x + 1;
 
         for mode in &modes {
@@ -0,0 +1,93 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::sequencer::note_pattern::{self, NotePattern, MAX_NOTE_LENGTH, STEPS_PER_BAR};
+use crate::ui::theme;
+
+/// One bar of a note pattern, pitches top to bottom around the cursor
+pub struct PianoRollWidget<'a> {
+    pub pattern: &'a NotePattern,
+    /// First step of the bar being shown
+    pub first_step: usize,
+    pub cursor_step: usize,
+    pub cursor_pitch: u8,
+    /// Playing step within the pattern
+    pub playhead: usize,
+    pub color: Color,
+}
+
+impl PianoRollWidget<'_> {
+    /// Whether a note of `pitch` started before `step` is still held there
+    fn is_held(&self, step: usize, pitch: u8) -> bool {
+        let from = step.saturating_sub(MAX_NOTE_LENGTH as usize - 1);
+        (from..step).any(|start| {
+            self.pattern
+                .get(start)
+                .is_some_and(|n| n.note == pitch && start + n.length as usize > step)
+        })
+    }
+}
+
+impl Widget for PianoRollWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 30 || area.height < 3 {
+            return;
+        }
+
+        let start_x = area.x + 5;
+        let step_width = ((area.width - 6) / STEPS_PER_BAR as u16).max(1);
+        let rows = area.height as i32;
+
+        // Keep the cursor pitch centered where the range allows
+        let top = (self.cursor_pitch as i32 + rows / 2).clamp(rows - 1, 127);
+
+        for row in 0..rows {
+            let pitch = top - row;
+            if pitch < 0 {
+                break;
+            }
+            let pitch = pitch as u8;
+            let y = area.y + row as u16;
+            let black_key = matches!(pitch % 12, 1 | 3 | 6 | 8 | 10);
+
+            let label_color = if pitch == self.cursor_pitch {
+                theme::ACCENT
+            } else if pitch.is_multiple_of(12) {
+                theme::FG
+            } else {
+                theme::DIM
+            };
+            buf.set_string(
+                area.x,
+                y,
+                format!("{:<4}", note_pattern::note_name(pitch)),
+                Style::default().fg(label_color),
+            );
+
+            for i in 0..STEPS_PER_BAR {
+                let step = self.first_step + i;
+                let x = start_x + i as u16 * step_width;
+                if x >= area.x + area.width || step >= self.pattern.len() {
+                    break;
+                }
+
+                let (ch, color) = match self.pattern.get(step) {
+                    Some(n) if n.note == pitch => ("■", self.color),
+                    _ if self.is_held(step, pitch) => ("─", self.color),
+                    _ if step == self.playhead => ("▪", theme::PLAYING_GREEN),
+                    _ if black_key => (" ", theme::DIM),
+                    _ if i % 4 == 0 => ("·", theme::FG),
+                    _ => ("·", theme::DIM),
+                };
+
+                let mut style = Style::default().fg(color);
+                if step == self.cursor_step && pitch == self.cursor_pitch {
+                    style = style.bg(theme::SELECTED_BG).fg(theme::ACCENT);
+                }
+                buf.set_string(x, y, ch, style);
+            }
+        }
+    }
+}
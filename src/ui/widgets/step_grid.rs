@@ -3,24 +3,34 @@ use ratatui::layout::Rect;
 use ratatui::style::Style;
 use ratatui::widgets::Widget;
 
+use crate::sequencer::pattern::DrumPattern;
 use crate::ui::theme;
 
-pub struct StepGridWidget {
-    /// patterns[instrument][step]
-    pub patterns: [[bool; 16]; 6],
+pub struct StepGridWidget<'a> {
+    pub pattern: &'a DrumPattern,
+    /// Steps into the pattern; each lane wraps it at its own length
     pub current_step: usize,
+    /// First step shown
+    pub first_step: usize,
+    /// Steps per beat and per bar, for the grid markers
+    pub beat_steps: usize,
+    pub bar_steps: usize,
     pub selected_instrument: usize,
+    /// Step being edited on the selected instrument's lane
+    pub selected_step: usize,
     pub instrument_names: [&'static str; 6],
+    pub mutes: [bool; 6],
 }
 
-impl Widget for StepGridWidget {
+impl Widget for StepGridWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         if area.width < 30 || area.height < 7 {
             return;
         }
 
+        // Room on the right for a lane's own length
         let start_x = area.x + 6;
-        let step_width = ((area.width - 7) / 16).max(1);
+        let step_width = ((area.width - 10) / 16).max(1);
 
         for (inst, name) in self.instrument_names.iter().enumerate() {
             let y = area.y + inst as u16;
@@ -29,36 +39,75 @@ impl Widget for StepGridWidget {
             }
 
             // Instrument label
-            let label_color = if inst == self.selected_instrument {
+            let label_color = if self.mutes[inst] {
+                theme::MUTE_YELLOW
+            } else if inst == self.selected_instrument {
                 theme::ACCENT
             } else {
                 theme::DIM
             };
-            buf.set_string(area.x, y, &format!("{:<5}", name), Style::default().fg(label_color));
+            buf.set_string(area.x, y, format!("{:<5}", name), Style::default().fg(label_color));
+
+            let lane_length = self.pattern.lane_length(inst);
+            let current = self.pattern.lane_step(inst, self.current_step);
 
             // Steps
-            for step in 0..16 {
-                let x = start_x + (step as u16 * step_width);
+            for column in 0..16 {
+                let step = self.first_step + column;
+                let x = start_x + (column as u16 * step_width);
                 if x >= area.x + area.width {
                     break;
                 }
+                if step >= lane_length {
+                    continue;
+                }
 
-                let is_active = self.patterns[inst][step];
-                let is_current = step == self.current_step;
+                let settings = self.pattern.lanes[inst].step(step).copied().unwrap_or_default();
+                let is_active = settings.active;
+                let is_current = step == current;
 
                 let (ch, color) = if is_active && is_current {
                     ("█", theme::ACCENT)
                 } else if is_active {
-                    ("■", theme::TRACK_COLORS[inst % 4])
+                    // Ratchets show their count, locked steps a diamond, soft steps a hollow square
+                    let ch = match settings.ratchets {
+                        2 => "2",
+                        3 => "3",
+                        4 => "4",
+                        _ if settings.has_locks() => "◆",
+                        _ if settings.velocity < 0.5 => "□",
+                        _ => "■",
+                    };
+                    // Steps that only sometimes play are dimmed
+                    let color = if settings.probability < 1.0 {
+                        theme::DIM
+                    } else {
+                        theme::TRACK_COLORS[inst % 4]
+                    };
+                    (ch, color)
                 } else if is_current {
                     ("▪", theme::PLAYING_GREEN)
-                } else if step % 4 == 0 {
+                } else if step.is_multiple_of(self.bar_steps) {
+                    ("•", theme::FG)
+                } else if step.is_multiple_of(self.beat_steps) {
                     ("·", theme::FG)
                 } else {
                     ("·", theme::DIM)
                 };
 
-                buf.set_string(x, y, ch, Style::default().fg(color));
+                let mut style = Style::default().fg(color);
+                if inst == self.selected_instrument && step == self.selected_step {
+                    style = style.bg(theme::SELECTED_BG);
+                }
+                buf.set_string(x, y, ch, style);
+            }
+
+            // Polymeter lanes show their own length
+            if lane_length != self.pattern.length {
+                let x = start_x + 16 * step_width + 1;
+                if x + 3 <= area.x + area.width {
+                    buf.set_string(x, y, format!("{:>2}", lane_length), Style::default().fg(theme::DIM));
+                }
             }
         }
     }
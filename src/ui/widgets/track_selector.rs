@@ -29,7 +29,7 @@ impl Widget for TrackSelectorWidget {
             } else {
                 Style::default().fg(theme::DIM)
             };
-            buf.set_string(x, y, &format!("T{}", i + 1), num_style);
+            buf.set_string(x, y, format!("T{}", i + 1), num_style);
             x += 2;
 
             // Status indicators